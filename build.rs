@@ -0,0 +1,38 @@
+use std::{env, fs, path::Path};
+
+/// Generate the lookup for the inputs embedded in the binary when the `embedded_inputs` feature is enabled.
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    if env::var_os("CARGO_FEATURE_EMBEDDED_INPUTS").is_none() {
+        return;
+    }
+
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("Cargo should set the manifest dir.");
+    let input_dir = Path::new(&manifest_dir).join("input");
+    println!("cargo:rerun-if-changed={}", input_dir.display());
+
+    let mut entries = String::new();
+    for day in 1..=25 {
+        let path = input_dir.join(format!("day{day}.txt"));
+        if path.is_file() {
+            entries.push_str(&format!(
+                "    ({}, include_str!({:?})),\n",
+                day,
+                path.display().to_string()
+            ));
+        }
+    }
+
+    let source = format!(
+        "/// The inputs found at build time, by day.\n\
+         const INPUTS: &[(i32, &str)] = &[\n{}];\n\n\
+         /// Get the input embedded for the day, if any.\n\
+         pub fn input_for_day(day: i32) -> Option<&'static str> {{\n    \
+         INPUTS.iter().find(|(d, _)| *d == day).map(|(_, data)| *data)\n}}\n",
+        entries
+    );
+
+    let out_dir = env::var("OUT_DIR").expect("Cargo should set the out dir.");
+    fs::write(Path::new(&out_dir).join("embedded_inputs.rs"), source)
+        .expect("Should be able to write the embedded inputs.");
+}
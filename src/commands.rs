@@ -0,0 +1,331 @@
+use std::{
+    io::BufRead,
+    time::{Duration, Instant},
+};
+
+use hashbrown::HashMap;
+
+use crate::problems::{
+    report::{Report, Value},
+    StreamSolver, Variant,
+};
+
+pub mod batch;
+pub mod bench;
+pub mod check;
+pub mod fetch;
+pub mod run;
+pub mod serve;
+
+/// Command line arguments split into positional values and `--flag` options.
+pub struct Args {
+    pub positional: Vec<String>,
+    options: HashMap<String, Option<String>>,
+}
+
+impl Args {
+    /// Parse the arguments. Flags listed in `value_flags` consume the following argument as value,
+    /// flags listed in `bool_flags` take none. Any other flag is rejected with the usage text.
+    pub fn parse(
+        args: &[String],
+        value_flags: &[&str],
+        bool_flags: &[&str],
+        usage: &str,
+    ) -> Result<Args, String> {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if !arg.starts_with("--") {
+                positional.push(arg.clone());
+            } else if value_flags.contains(&arg.as_str()) {
+                let value = iter
+                    .next()
+                    .ok_or(format!("Missing value for option {}", arg))?;
+                options.insert(arg.clone(), Some(value.clone()));
+            } else if bool_flags.contains(&arg.as_str()) {
+                options.insert(arg.clone(), None);
+            } else {
+                return Err(format!("Unknown option {}\n{}", arg, usage));
+            }
+        }
+
+        Ok(Args {
+            positional,
+            options,
+        })
+    }
+
+    /// Get the value of an option, if it was given.
+    pub fn value(&self, flag: &str) -> Option<&str> {
+        self.options.get(flag)?.as_deref()
+    }
+
+    /// Check if a flag was given.
+    pub fn has(&self, flag: &str) -> bool {
+        self.options.contains_key(flag)
+    }
+}
+
+/// Parse the day number from a command line argument.
+pub fn parse_day(arg: &str) -> Result<i32, String> {
+    arg.parse::<i32>()
+        .map_err(|_| format!("Failed to parse a day from {}", arg))
+}
+
+/// Parse an optional positive count from an option value, with a default if not given.
+pub fn parse_count(value: Option<&str>, default: usize) -> Result<usize, String> {
+    match value {
+        Some(value) => value
+            .parse::<usize>()
+            .ok()
+            .filter(|&count| count > 0)
+            .ok_or(format!("Expected a positive number but got {}", value)),
+        None => Ok(default),
+    }
+}
+
+/// Outcome of running a single variant of a day.
+pub struct VariantRun {
+    pub name: &'static str,
+    pub result: Result<Vec<u64>, String>,
+    pub elapsed: Duration,
+}
+
+/// Run each variant on the input and time it.
+pub fn run_variants(variants: &[Variant], input_data: &[String]) -> Vec<VariantRun> {
+    variants
+        .iter()
+        .map(|variant| {
+            let before = Instant::now();
+            let result = (variant.solve)(input_data);
+            VariantRun {
+                name: variant.name,
+                result,
+                elapsed: before.elapsed(),
+            }
+        })
+        .collect()
+}
+
+/// Run the streaming solver of a day on the reader and time it, as a run named `stream`.
+pub fn run_stream(solve: StreamSolver, reader: &mut dyn BufRead) -> VariantRun {
+    let before = Instant::now();
+    let result = solve(reader);
+    VariantRun {
+        name: "stream",
+        result,
+        elapsed: before.elapsed(),
+    }
+}
+
+/// Check that all variants succeeded with the same answers, and return them.
+pub fn check_agreement(runs: &[VariantRun]) -> Result<Vec<u64>, String> {
+    let mut answers: Option<(&str, &Vec<u64>)> = None;
+    for run in runs {
+        let result = run
+            .result
+            .as_ref()
+            .map_err(|err| format!("Variant {} failed: {}", run.name, err))?;
+        match answers {
+            Some((name, expected)) if expected != result => {
+                return Err(format!(
+                    "Variants disagree: {} gave {:?} but {} gave {:?}",
+                    name, expected, run.name, result
+                ))
+            }
+            Some(_) => (),
+            None => answers = Some((run.name, result)),
+        }
+    }
+
+    answers
+        .map(|(_, result)| result.clone())
+        .ok_or("No variants to run".to_owned())
+}
+
+/// Format rows of cells as a table with aligned columns.
+pub fn format_table<const N: usize>(header: [&str; N], rows: &[[String; N]]) -> String {
+    let rows: Vec<Vec<String>> = rows.iter().map(|row| row.to_vec()).collect();
+    format_rows(&header, &rows)
+}
+
+/// Format rows of cells as a table with aligned columns, for any number of columns.
+pub fn format_rows(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|cell| cell.len()).collect();
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let mut table = String::new();
+    let header: Vec<String> = header.iter().map(|&cell| cell.to_owned()).collect();
+    for row in std::iter::once(&header).chain(rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        table.push_str(cells.join(" | ").trim_end());
+        table.push('\n');
+    }
+
+    table
+}
+
+/// Format a report as a readable text, with a table for each section.
+pub fn format_report(report: &Report) -> String {
+    let mut text = String::new();
+    for section in report.sections.iter() {
+        text.push_str(&format!(
+            "{} ({} rows)\n",
+            section.title,
+            section.rows.len()
+        ));
+        let rows: Vec<Vec<String>> = section
+            .rows
+            .iter()
+            .map(|row| row.iter().map(Value::to_string).collect())
+            .collect();
+        text.push_str(&format_rows(&section.columns, &rows));
+    }
+
+    text
+}
+
+/// Encode a report as JSON, with each row as an object keyed by the column names.
+pub fn report_json(report: &Report) -> String {
+    let sections: Vec<String> = report
+        .sections
+        .iter()
+        .map(|section| {
+            let rows: Vec<String> = section
+                .rows
+                .iter()
+                .map(|row| {
+                    let fields: Vec<String> = section
+                        .columns
+                        .iter()
+                        .zip(row)
+                        .map(|(column, value)| {
+                            let value = match value {
+                                Value::Number(val) => val.to_string(),
+                                Value::Text(text) => json_string(text),
+                                Value::Empty => "null".to_owned(),
+                            };
+                            format!("{}:{}", json_string(column), value)
+                        })
+                        .collect();
+                    format!("{{{}}}", fields.join(","))
+                })
+                .collect();
+            format!(
+                "{{\"title\":{},\"rows\":[{}]}}",
+                json_string(&section.title),
+                rows.join(",")
+            )
+        })
+        .collect();
+
+    format!("{{\"sections\":[{}]}}", sections.join(","))
+}
+
+/// Encode a report as CSV. Each section starts with its title on a line of its own, followed by
+/// the header and the rows, and sections are separated by an empty line.
+pub fn report_csv(report: &Report) -> String {
+    let sections: Vec<String> = report
+        .sections
+        .iter()
+        .map(|section| {
+            let mut lines = vec![csv_field(&section.title)];
+            let header: Vec<String> = section.columns.iter().map(|c| csv_field(c)).collect();
+            lines.push(header.join(","));
+            for row in section.rows.iter() {
+                let fields: Vec<String> = row
+                    .iter()
+                    .map(|value| match value {
+                        Value::Empty => String::new(),
+                        value => csv_field(&value.to_string()),
+                    })
+                    .collect();
+                lines.push(fields.join(","));
+            }
+            lines.join("\n") + "\n"
+        })
+        .collect();
+
+    sections.join("\n")
+}
+
+/// Quote a CSV field if it contains a separator, a quote or a line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+/// Format a duration in milliseconds with microsecond precision.
+pub fn format_duration(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs_f64() * 1000.)
+}
+
+/// Encode a string as a quoted JSON string literal.
+pub fn json_string(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len() + 2);
+    encoded.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => encoded.push_str("\\\""),
+            '\\' => encoded.push_str("\\\\"),
+            '\n' => encoded.push_str("\\n"),
+            '\r' => encoded.push_str("\\r"),
+            '\t' => encoded.push_str("\\t"),
+            c if (c as u32) < 0x20 => encoded.push_str(&format!("\\u{:04x}", c as u32)),
+            c => encoded.push(c),
+        }
+    }
+    encoded.push('"');
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USAGE: &str = "Usage: <day> [--variant <name>] [--explain]";
+
+    fn to_args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = Args::parse(
+            &to_args(&["5", "--variant", "fast", "--explain"]),
+            &["--variant"],
+            &["--explain"],
+            USAGE,
+        )
+        .unwrap();
+        assert_eq!(args.positional, vec!["5".to_owned()]);
+        assert_eq!(args.value("--variant"), Some("fast"));
+        assert!(args.has("--explain"));
+        assert!(!args.has("--json"));
+
+        let err = Args::parse(
+            &to_args(&["5", "--varient", "fast"]),
+            &["--variant"],
+            &["--explain"],
+            USAGE,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err, format!("Unknown option --varient\n{}", USAGE));
+
+        assert!(Args::parse(&to_args(&["5", "--variant"]), &["--variant"], &[], USAGE).is_err());
+    }
+}
@@ -0,0 +1,220 @@
+use std::{
+    fs,
+    io::BufReader,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crate::problems::{solve_day, stream_solver_for_day, utils};
+
+/// Extension for files holding the expected answers for an input with the same stem.
+const ANSWERS_EXTENSION: &str = "answers";
+
+/// Outcome of running a day against a single input file.
+pub struct BatchEntry {
+    pub name: String,
+    pub result: Result<Vec<u64>, String>,
+    pub elapsed: Duration,
+    /// The expected answers, or the error reading them, if there is an answers file.
+    pub expected: Option<Result<Vec<u64>, String>>,
+}
+
+impl BatchEntry {
+    /// Compare the answers with the expected ones. Returns [`None`] if there is no answers file.
+    /// An empty or unreadable answers file fails.
+    pub fn passed(&self) -> Option<bool> {
+        let expected = self.expected.as_ref()?;
+        Some(match (expected, &self.result) {
+            (Ok(expected), Ok(answers)) => !expected.is_empty() && expected == answers,
+            _ => false,
+        })
+    }
+}
+
+/// Run the `batch <day> <dir>` command and print the results as a table.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let (day, dir) = match args {
+        [day, dir] => (super::parse_day(day)?, Path::new(dir)),
+        _ => return Err("Usage: batch <day> <dir>".to_owned()),
+    };
+
+    println!("Running day {} for all inputs in {}", day, dir.display());
+    let entries = run_batch(day, dir)?;
+    print!("{}", format_entries(&entries));
+
+    let checked: Vec<bool> = entries.iter().filter_map(BatchEntry::passed).collect();
+    if !checked.is_empty() {
+        println!(
+            "{} of {} checked inputs passed",
+            checked.iter().filter(|&&passed| passed).count(),
+            checked.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Run the solver for the day against every input file in the directory.
+pub fn run_batch(day: i32, dir: &Path) -> Result<Vec<BatchEntry>, String> {
+    let inputs = find_input_files(dir)?;
+    if inputs.is_empty() {
+        return Err(format!("No input files found in {}", dir.display()));
+    }
+
+    inputs
+        .iter()
+        .map(|path| run_single_input(day, path))
+        .collect()
+}
+
+/// Run the solver for a single input file, and read the expected answers next to it if present.
+/// Days with a stream hook read the file as they solve it, instead of loading the lines first.
+fn run_single_input(day: i32, path: &Path) -> Result<BatchEntry, String> {
+    let read_error = |_| format!("Failed to read input data from {}", path.display());
+    let (result, elapsed) = match stream_solver_for_day(day) {
+        Ok(solve) => {
+            let file = fs::File::open(path).map_err(read_error)?;
+            let before = Instant::now();
+            let result = solve(&mut BufReader::new(file));
+            (result, before.elapsed())
+        }
+        Err(_) => {
+            let input_data = utils::read_input_from_path(path).map_err(read_error)?;
+            let before = Instant::now();
+            let result = solve_day(day, &input_data);
+            (result, before.elapsed())
+        }
+    };
+
+    let answers_path = path.with_extension(ANSWERS_EXTENSION);
+    let expected = if answers_path.is_file() {
+        Some(parse_answers(&answers_path))
+    } else {
+        None
+    };
+
+    Ok(BatchEntry {
+        name: file_name(path),
+        result,
+        elapsed,
+        expected,
+    })
+}
+
+/// Find all input files in the directory, skipping the answers files. Sorted by name.
+fn find_input_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut inputs: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|_| format!("Failed to read directory {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && path.extension().and_then(|ext| ext.to_str()) != Some(ANSWERS_EXTENSION)
+        })
+        .collect();
+
+    inputs.sort();
+    Ok(inputs)
+}
+
+/// Parse an answers file, with one answer per line in the same order as the solver output.
+fn parse_answers(path: &Path) -> Result<Vec<u64>, String> {
+    utils::read_input_from_path(path)
+        .map_err(|_| format!("Failed to read answers from {}", path.display()))?
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.parse::<u64>()
+                .map_err(|_| format!("Failed to parse answer {} in {}", line, path.display()))
+        })
+        .collect()
+}
+
+/// Get the file name of a path as a string.
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Format the entries as a table with the answers, timing and check status per input.
+pub fn format_entries(entries: &[BatchEntry]) -> String {
+    let rows: Vec<[String; 4]> = entries
+        .iter()
+        .map(|entry| {
+            let answers = match &entry.result {
+                Ok(answers) => answers
+                    .iter()
+                    .map(|a| a.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                Err(err) => format!("error: {}", err),
+            };
+            let status = match (entry.passed(), &entry.expected) {
+                (Some(true), _) => "PASS".to_owned(),
+                (_, Some(Err(err))) => format!("FAIL: {}", err),
+                (Some(false), _) => "FAIL".to_owned(),
+                (None, _) => "-".to_owned(),
+            };
+            [
+                entry.name.clone(),
+                answers,
+                super::format_duration(entry.elapsed),
+                status,
+            ]
+        })
+        .collect();
+
+    super::format_table(["Input", "Answers", "Time", "Status"], &rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_with_answers() {
+        let dir = std::env::temp_dir().join(format!("aoc24-batch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let example = "MMMSXXMASM\nMSAMXMSMSA\nAMXSXMAAMM\nMSAMASMSMX\nXMASAMXAMM\n\
+                       XXAMMXXAMA\nSMSMSASXSS\nSAXAMASAAA\nMAMMMXMMMM\nMXMXAXMASX\n";
+        fs::write(dir.join("alice.txt"), example).unwrap();
+        fs::write(dir.join("alice.answers"), "18\n9\n").unwrap();
+        fs::write(dir.join("bob.txt"), example).unwrap();
+        fs::write(dir.join("bob.answers"), "18\n10\n").unwrap();
+        fs::write(dir.join("carol.txt"), "XMAS\n").unwrap();
+        fs::write(dir.join("dave.txt"), example).unwrap();
+        fs::write(dir.join("dave.answers"), "18\n").unwrap();
+        fs::write(dir.join("erin.txt"), example).unwrap();
+        fs::write(dir.join("erin.answers"), "").unwrap();
+        fs::write(dir.join("frank.txt"), example).unwrap();
+        fs::write(dir.join("frank.answers"), "18\nnine\n").unwrap();
+
+        let entries = run_batch(4, &dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(
+            vec![
+                "alice.txt",
+                "bob.txt",
+                "carol.txt",
+                "dave.txt",
+                "erin.txt",
+                "frank.txt"
+            ],
+            names
+        );
+        assert_eq!(Some(true), entries[0].passed());
+        assert_eq!(Some(false), entries[1].passed());
+        assert_eq!(None, entries[2].passed());
+        assert_eq!(&vec![1, 0], entries[2].result.as_ref().unwrap());
+
+        // Truncated, empty and malformed answers files fail without stopping the batch
+        assert_eq!(Some(false), entries[3].passed());
+        assert_eq!(Some(false), entries[4].passed());
+        assert_eq!(Some(false), entries[5].passed());
+        assert!(format_entries(&entries).contains("FAIL: Failed to parse answer nine"));
+    }
+}
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use crate::problems::{find_variant, utils, variants_for_day};
+
+use super::{
+    check_agreement, format_duration, format_table, parse_count, parse_day, run_variants, Args,
+};
+
+const USAGE: &str = "Usage: bench <day> [--runs <n>] [--variant <name>]";
+
+/// Default number of timed runs per variant.
+const DEFAULT_RUNS: usize = 10;
+
+/// Timing statistics for a variant over several runs.
+pub struct BenchResult {
+    pub name: &'static str,
+    pub min: Duration,
+    pub mean: Duration,
+}
+
+/// Run the `bench` command, timing every variant of a day over several runs.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &["--runs", "--variant"], &[], USAGE)?;
+    let day = match args.positional.as_slice() {
+        [day] => parse_day(day)?,
+        _ => return Err(USAGE.to_owned()),
+    };
+    let num_runs = parse_count(args.value("--runs"), DEFAULT_RUNS)?;
+    let variants = match args.value("--variant") {
+        Some(name) => vec![find_variant(day, name)?],
+        None => variants_for_day(day)?,
+    };
+
+    println!("Benchmarking day {} over {} runs", day, num_runs);
+    let input_data = utils::load_input_for_day(day)?;
+
+    let results = bench_variants(&variants, &input_data, num_runs)?;
+    print!("{}", format_bench_results(&results));
+
+    Ok(())
+}
+
+/// Time each variant over a number of runs. Fails if any run disagrees with the others.
+pub fn bench_variants(
+    variants: &[crate::problems::Variant],
+    input_data: &[String],
+    num_runs: usize,
+) -> Result<Vec<BenchResult>, String> {
+    let mut timings = vec![Vec::with_capacity(num_runs); variants.len()];
+    for _ in 0..num_runs {
+        let runs = run_variants(variants, input_data);
+        check_agreement(&runs)?;
+        for (timing, run) in timings.iter_mut().zip(runs) {
+            timing.push(run.elapsed);
+        }
+    }
+
+    Ok(variants
+        .iter()
+        .zip(timings)
+        .map(|(variant, timing)| BenchResult {
+            name: variant.name,
+            min: timing.iter().min().copied().unwrap_or_default(),
+            mean: timing.iter().sum::<Duration>() / timing.len() as u32,
+        })
+        .collect())
+}
+
+/// Format the results as a table, with the mean time relative to the fastest variant.
+pub fn format_bench_results(results: &[BenchResult]) -> String {
+    let fastest = results
+        .iter()
+        .map(|result| result.mean)
+        .min()
+        .unwrap_or_default();
+
+    let rows: Vec<[String; 4]> = results
+        .iter()
+        .map(|result| {
+            [
+                result.name.to_owned(),
+                format_duration(result.min),
+                format_duration(result.mean),
+                format!(
+                    "{:.2}x",
+                    result.mean.as_secs_f64() / fastest.as_secs_f64().max(f64::EPSILON)
+                ),
+            ]
+        })
+        .collect();
+
+    format_table(["Variant", "Min", "Mean", "Relative"], &rows)
+}
@@ -0,0 +1,38 @@
+use crate::problems::{utils, validator_for_day};
+
+use super::parse_day;
+
+const USAGE: &str = "Usage: check <day> [<file>]";
+
+/// Run the `check <day> [<file>]` command, listing every structural problem in the input.
+/// Checks the input of the day from the input folder, unless another file is given.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let (day, path) = match args {
+        [day] => (parse_day(day)?, None),
+        [day, path] => (parse_day(day)?, Some(path)),
+        _ => return Err(USAGE.to_owned()),
+    };
+    let validate = validator_for_day(day)?;
+
+    let input_data = match path {
+        Some(path) => utils::read_input_from_path(path)
+            .map_err(|_| format!("Failed to read input file {}", path))?,
+        None => utils::load_input_for_day(day)?,
+    };
+
+    let mut diagnostics = validate(&input_data);
+    diagnostics.sort_by_key(|diagnostic| diagnostic.line);
+    if diagnostics.is_empty() {
+        println!("No problems found in the input for day {}", day);
+        return Ok(());
+    }
+
+    for diagnostic in diagnostics.iter() {
+        println!("{}", diagnostic);
+    }
+    Err(format!(
+        "Found {} problems in the input for day {}",
+        diagnostics.len(),
+        day
+    ))
+}
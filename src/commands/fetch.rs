@@ -0,0 +1,271 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::{parse_day, Args};
+
+const USAGE: &str = "Usage: fetch <day> [--config <path>]";
+
+/// Default location of the config file with the session token.
+const DEFAULT_CONFIG_PATH: &str = "aoc.conf";
+
+/// Folder where the inputs are cached.
+const INPUT_DIR: &str = "input";
+
+const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+const DEFAULT_YEAR: u32 = 2024;
+const USER_AGENT: &str = "github.com/joelhi/advent-of-code-24 input fetcher";
+
+/// Settings for downloading inputs, read from a config file with `key = value` lines.
+pub struct FetchConfig {
+    pub session: String,
+    pub base_url: String,
+    pub year: u32,
+}
+
+/// Result of fetching an input.
+#[derive(Debug, PartialEq)]
+pub enum FetchOutcome {
+    Cached(PathBuf),
+    Downloaded(PathBuf),
+}
+
+impl FetchConfig {
+    /// Read the config from a file.
+    pub fn from_file(path: &Path) -> Result<FetchConfig, String> {
+        let text = fs::read_to_string(path).map_err(|_| {
+            format!(
+                "Failed to read config file {}. It should contain a line `session = <token>`",
+                path.display()
+            )
+        })?;
+        FetchConfig::parse(&text)
+    }
+
+    /// Parse the config from text. Blank lines and lines starting with `#` are ignored.
+    pub fn parse(text: &str) -> Result<FetchConfig, String> {
+        let mut session = None;
+        let mut base_url = DEFAULT_BASE_URL.to_owned();
+        let mut year = DEFAULT_YEAR;
+        for (line_id, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(format!("Line {}: Expected `key = value`", line_id + 1))?;
+            let value = value.trim();
+            match key.trim() {
+                "session" => session = Some(value.to_owned()),
+                "base_url" => base_url = value.trim_end_matches('/').to_owned(),
+                "year" => {
+                    year = value
+                        .parse()
+                        .map_err(|_| format!("Line {}: Invalid year {}", line_id + 1, value))?
+                }
+                key => return Err(format!("Line {}: Unknown key {}", line_id + 1, key)),
+            }
+        }
+
+        Ok(FetchConfig {
+            session: session.ok_or("No session token found in config")?,
+            base_url,
+            year,
+        })
+    }
+}
+
+/// Run the `fetch` command, downloading the input for a day unless it is already cached.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &["--config"], &[], USAGE)?;
+    let day = match args.positional.as_slice() {
+        [day] => parse_day(day)?,
+        _ => return Err(USAGE.to_owned()),
+    };
+    let config_path = Path::new(args.value("--config").unwrap_or(DEFAULT_CONFIG_PATH));
+    let input_path = Path::new(INPUT_DIR).join(format!("day{day}.txt"));
+    if input_path.is_file() {
+        println!(
+            "Input for day {} is already cached at {}",
+            day,
+            input_path.display()
+        );
+        return Ok(());
+    }
+
+    let config = FetchConfig::from_file(config_path)?;
+    match fetch_input(&config, day, Path::new(INPUT_DIR))? {
+        FetchOutcome::Cached(path) => {
+            println!(
+                "Input for day {} is already cached at {}",
+                day,
+                path.display()
+            )
+        }
+        FetchOutcome::Downloaded(path) => {
+            println!("Downloaded input for day {} to {}", day, path.display())
+        }
+    }
+
+    Ok(())
+}
+
+/// Download the input for the day into the folder, unless it is already there.
+pub fn fetch_input(
+    config: &FetchConfig,
+    day: i32,
+    input_dir: &Path,
+) -> Result<FetchOutcome, String> {
+    if !(1..=25).contains(&day) {
+        return Err(format!(
+            "Number {} is not a valid day for the calendar...",
+            day
+        ));
+    }
+
+    let path = input_dir.join(format!("day{day}.txt"));
+    if path.is_file() {
+        return Ok(FetchOutcome::Cached(path));
+    }
+
+    let url = format!("{}/{}/day/{}/input", config.base_url, config.year, day);
+    let input = download(&url, &config.session)?;
+
+    // Write to a temporary file first, so a failed write never leaves a partial input behind.
+    fs::create_dir_all(input_dir)
+        .map_err(|_| format!("Failed to create folder {}", input_dir.display()))?;
+    let temp_path = path.with_extension("txt.part");
+    fs::write(&temp_path, input).map_err(|_| format!("Failed to write {}", temp_path.display()))?;
+    fs::rename(&temp_path, &path).map_err(|_| format!("Failed to write {}", path.display()))?;
+
+    Ok(FetchOutcome::Downloaded(path))
+}
+
+/// Get the body of the url, authenticated with the session token.
+fn download(url: &str, session: &str) -> Result<String, String> {
+    let mut response = ureq::get(url)
+        .header("Cookie", &format!("session={}", session))
+        .header("User-Agent", USER_AGENT)
+        .call()
+        .map_err(|err| match err {
+            ureq::Error::StatusCode(code) => {
+                format!("Request to {} failed with status {}", url, code)
+            }
+            err => format!("Request to {} failed: {}", url, err),
+        })?;
+
+    response
+        .body_mut()
+        .read_to_string()
+        .map_err(|err| format!("Failed to read response from {}: {}", url, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    /// Start a mock server answering every request with the input if the session matches.
+    /// Returns the base url and the list of received request lines.
+    fn start_mock_server(
+        session: &'static str,
+        input: &'static str,
+    ) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = Arc::clone(&requests);
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(&stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut authorized = false;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim_end().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        authorized |= name.eq_ignore_ascii_case("cookie")
+                            && value.trim() == format!("session={}", session);
+                    }
+                }
+                received
+                    .lock()
+                    .unwrap()
+                    .push(request_line.trim_end().to_owned());
+
+                let (status, body) = if authorized {
+                    ("200 OK", input)
+                } else {
+                    ("400 Bad Request", "Puzzle inputs differ by user.")
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+
+        (base_url, requests)
+    }
+
+    #[test]
+    fn test_fetch_and_cache() {
+        let (base_url, requests) = start_mock_server("abc123", "3   4\n4   3\n");
+        let dir = std::env::temp_dir().join(format!("aoc24-fetch-{}", std::process::id()));
+        let config =
+            FetchConfig::parse(&format!("session = abc123\nbase_url = {}\n", base_url)).unwrap();
+
+        let first = fetch_input(&config, 1, &dir).unwrap();
+        let second = fetch_input(&config, 1, &dir).unwrap();
+        let content = fs::read_to_string(dir.join("day1.txt")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(FetchOutcome::Downloaded(dir.join("day1.txt")), first);
+        assert_eq!(FetchOutcome::Cached(dir.join("day1.txt")), second);
+        assert_eq!("3   4\n4   3\n", content);
+        assert_eq!(
+            vec!["GET /2024/day/1/input HTTP/1.1".to_owned()],
+            *requests.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_fetch_with_invalid_session() {
+        let (base_url, _) = start_mock_server("abc123", "3   4\n");
+        let dir = std::env::temp_dir().join(format!("aoc24-fetch-invalid-{}", std::process::id()));
+        let config =
+            FetchConfig::parse(&format!("session = wrong\nbase_url = {}\n", base_url)).unwrap();
+
+        let result = fetch_input(&config, 2, &dir);
+
+        assert!(result.is_err());
+        assert!(!dir.join("day2.txt").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = FetchConfig::parse("# token\nsession = abc\nyear = 2023\n").unwrap();
+        assert_eq!("abc", config.session);
+        assert_eq!(2023, config.year);
+        assert_eq!(DEFAULT_BASE_URL, config.base_url);
+
+        assert!(FetchConfig::parse("year = 2023\n").is_err());
+        assert!(FetchConfig::parse("token abc\n").is_err());
+    }
+}
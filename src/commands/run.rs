@@ -0,0 +1,158 @@
+use std::{fs, path::Path, time::Instant};
+
+use crate::problems::{
+    explainer_for_day, find_variant, frame_exporter_for_day,
+    image::{FrameWriter, Image, ImageFormat},
+    renderer_for_day, stream_solver_for_day, utils, variants_for_day, FrameExporter, Renderer,
+};
+
+use super::{
+    check_agreement, format_duration, format_report, format_table, parse_count, parse_day,
+    report_csv, report_json, run_stream, run_variants, Args,
+};
+
+const USAGE: &str = "Usage: <day> [--variant <name>] [--render [--colour]] \
+                     [--export <dir> [--frames <n>] [--scale <n>]] [--explain [--json | --csv]]";
+
+/// Default number of pixels per cell in exported images.
+const DEFAULT_SCALE: usize = 4;
+
+/// Image export requested on the command line.
+enum Export {
+    FinalState(Renderer),
+    Frames(FrameExporter, usize),
+}
+
+/// Run the solution for a single day with the input from the input folder.
+/// Runs every variant of the day unless a single one is selected with `--variant`.
+/// With `--render` the final state of grid days is printed after the answers, and with
+/// `--export` it is written as an image, or as a sequence of frames with `--frames`.
+/// With `--explain` a report of intermediate facts is printed, as JSON with `--json` or as CSV
+/// with `--csv`. Days with a stream hook read their input as they solve it instead of loading
+/// the lines, unless one of these options or a single variant is given.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(
+        args,
+        &["--variant", "--export", "--frames", "--scale"],
+        &["--render", "--colour", "--explain", "--json", "--csv"],
+        USAGE,
+    )?;
+    let day = match args.positional.as_slice() {
+        [day] => parse_day(day)?,
+        _ => return Err(USAGE.to_owned()),
+    };
+    let variants = match args.value("--variant") {
+        Some(name) => vec![find_variant(day, name)?],
+        None => variants_for_day(day)?,
+    };
+
+    let renderer = if args.has("--render") {
+        Some(renderer_for_day(day)?)
+    } else {
+        None
+    };
+    let export = match (args.value("--export"), args.value("--frames")) {
+        (Some(_), Some(frames)) => Some(Export::Frames(
+            frame_exporter_for_day(day)?,
+            parse_count(Some(frames), 0)?,
+        )),
+        (Some(_), None) => Some(Export::FinalState(renderer_for_day(day)?)),
+        (None, _) => None,
+    };
+    let scale = parse_count(args.value("--scale"), DEFAULT_SCALE)?;
+    let explainer = if args.has("--explain") {
+        if args.has("--json") && args.has("--csv") {
+            return Err(USAGE.to_owned());
+        }
+        Some(explainer_for_day(day)?)
+    } else if args.has("--json") || args.has("--csv") {
+        return Err(USAGE.to_owned());
+    } else {
+        None
+    };
+
+    let streaming = match (args.value("--variant"), &renderer, &export, &explainer) {
+        (None, None, None, None) => stream_solver_for_day(day).ok(),
+        _ => None,
+    };
+
+    let before = Instant::now();
+    println!("Running day {}", day);
+    let mut input_data = Vec::new();
+    let runs = match streaming {
+        Some(solve) => vec![run_stream(solve, &mut utils::open_input_for_day(day)?)],
+        None => {
+            input_data = utils::load_input_for_day(day)?;
+            run_variants(&variants, &input_data)
+        }
+    };
+    if runs.len() > 1 {
+        let rows: Vec<[String; 3]> = runs
+            .iter()
+            .map(|run| {
+                let answers = match &run.result {
+                    Ok(answers) => format!("{:?}", answers),
+                    Err(err) => format!("error: {}", err),
+                };
+                [run.name.to_owned(), format_duration(run.elapsed), answers]
+            })
+            .collect();
+        print!("{}", format_table(["Variant", "Time", "Answers"], &rows));
+    }
+
+    let result = check_agreement(&runs)?;
+    println!("The answers are:");
+    for (i, val) in result.iter().enumerate() {
+        println!("{}: {}", i, val);
+    }
+    println!("Solution completed in {:2}ms", before.elapsed().as_millis());
+
+    if let Some(render) = renderer {
+        print!("{}", render(&input_data)?.render(args.has("--colour")));
+    }
+
+    if let Some(explain) = explainer {
+        let report = explain(&input_data)?;
+        if args.has("--json") {
+            println!("{}", report_json(&report));
+        } else if args.has("--csv") {
+            print!("{}", report_csv(&report));
+        } else {
+            print!("{}", format_report(&report));
+        }
+    }
+
+    if let (Some(export), Some(dir)) = (export, args.value("--export")) {
+        export_images(day, &input_data, export, Path::new(dir), scale)?;
+    }
+
+    Ok(())
+}
+
+/// Write the final state or the frames of the day as images to the folder.
+fn export_images(
+    day: i32,
+    input_data: &[String],
+    export: Export,
+    dir: &Path,
+    scale: usize,
+) -> Result<(), String> {
+    match export {
+        Export::FinalState(render) => {
+            fs::create_dir_all(dir)
+                .map_err(|_| format!("Failed to create folder {}", dir.display()))?;
+            let path = dir.join(format!("day{}.{}", day, ImageFormat::Ppm.extension()));
+            Image::from_canvas(&render(input_data)?)
+                .scaled(scale)
+                .write(&path, ImageFormat::Ppm)?;
+            println!("Exported final state to {}", path.display());
+        }
+        Export::Frames(export_frames, max_frames) => {
+            let mut writer = FrameWriter::new(dir, scale)?;
+            export_frames(input_data, &mut writer, max_frames)?;
+            println!("Exported {} frames to {}", writer.count(), dir.display());
+        }
+    }
+
+    Ok(())
+}
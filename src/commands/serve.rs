@@ -0,0 +1,266 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    panic, thread,
+    time::Duration,
+};
+
+use crate::problems::{find_variant, stream_solver_for_day, variants_for_day};
+
+use super::{check_agreement, json_string, parse_day, run_stream, run_variants, Args};
+
+const USAGE: &str = "Usage: serve [--port <port>]";
+
+/// Port used when none is given.
+const DEFAULT_PORT: u16 = 8024;
+
+/// Upper limit for the size of a request body.
+const MAX_BODY_LEN: usize = 16 * 1024 * 1024;
+
+/// Time to wait for the client to send more of the request before giving up.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Run the `serve` command, answering `POST /solve/{day}` requests on localhost.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &["--port"], &[], USAGE)?;
+    if !args.positional.is_empty() {
+        return Err(USAGE.to_owned());
+    }
+    let port = match args.value("--port") {
+        Some(port) => port
+            .parse::<u16>()
+            .map_err(|_| format!("Invalid port: {}", port))?,
+        None => DEFAULT_PORT,
+    };
+
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .map_err(|err| format!("Failed to bind to port {}: {}", port, err))?;
+    println!("Listening on http://127.0.0.1:{}", port);
+    serve(listener);
+
+    Ok(())
+}
+
+/// Accept connections on the listener, handling each on its own thread.
+pub fn serve(listener: TcpListener) {
+    for stream in listener.incoming().flatten() {
+        thread::spawn(move || {
+            if let Err(err) = handle_connection(stream) {
+                println!("Failed to handle request: {}", err);
+            }
+        });
+    }
+}
+
+/// Read a single request from the stream and write the response.
+fn handle_connection(mut stream: TcpStream) -> Result<(), String> {
+    stream
+        .set_read_timeout(Some(READ_TIMEOUT))
+        .map_err(|err| format!("Failed to set read timeout: {}", err))?;
+    let (status, body) = match read_request(&mut stream) {
+        Ok((method, path, body)) => handle_request(&method, &path, &body),
+        Err(err) => (400, error_json(&err)),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason_phrase(status),
+        body.len(),
+        body
+    );
+    stream
+        .write_all(response.as_bytes())
+        .map_err(|err| format!("Failed to write response: {}", err))
+}
+
+/// Read the method, path and body of an HTTP request.
+fn read_request(stream: &mut TcpStream) -> Result<(String, String, String), String> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader
+        .read_line(&mut request_line)
+        .map_err(|_| "Failed to read request line")?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().ok_or("Missing request method")?.to_owned();
+    let path = parts.next().ok_or("Missing request path")?.to_owned();
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        reader
+            .read_line(&mut header)
+            .map_err(|_| "Failed to read request header")?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid content length: {}", value.trim()))?;
+            }
+        }
+    }
+
+    if content_length > MAX_BODY_LEN {
+        return Err(format!(
+            "Request body is too large: {} bytes",
+            content_length
+        ));
+    }
+    let mut body = vec![0; content_length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|_| "Failed to read request body")?;
+    let body = String::from_utf8(body).map_err(|_| "Request body is not valid UTF-8")?;
+
+    Ok((method, path, body))
+}
+
+/// Route a request and compute the status code and JSON response body.
+pub fn handle_request(method: &str, path: &str, body: &str) -> (u16, String) {
+    let (route, query) = path.split_once('?').unwrap_or((path, ""));
+    let day = match route.strip_prefix("/solve/") {
+        Some(day) => day,
+        None => return (404, error_json(&format!("No route for {}", route))),
+    };
+    if method != "POST" {
+        return (405, error_json("Only POST is supported for /solve/{day}"));
+    }
+
+    respond(|| solve_request(day, query, body))
+}
+
+/// Compute the status code and body for a solve. A panic in the solution is caught and reported
+/// as an internal error instead of dropping the connection.
+fn respond<F>(solve: F) -> (u16, String)
+where
+    F: FnOnce() -> Result<String, String> + panic::UnwindSafe,
+{
+    match panic::catch_unwind(solve) {
+        Ok(Ok(response)) => (200, response),
+        Ok(Err(err)) => (400, error_json(&err)),
+        Err(_) => (500, error_json("The solution panicked on this input")),
+    }
+}
+
+/// Solve the day with the request body as input. A variant can be selected with `?variant=name`,
+/// otherwise days with a stream hook solve the body without splitting it into lines.
+fn solve_request(day: &str, query: &str, body: &str) -> Result<String, String> {
+    let day = parse_day(day)?;
+    let variant = query
+        .split('&')
+        .find_map(|param| param.strip_prefix("variant="));
+    let runs = match (variant, stream_solver_for_day(day)) {
+        (None, Ok(solve)) => vec![run_stream(solve, &mut body.as_bytes())],
+        (Some(name), _) => {
+            let input_data: Vec<String> = body.lines().map(String::from).collect();
+            run_variants(&[find_variant(day, name)?], &input_data)
+        }
+        (None, Err(_)) => {
+            let input_data: Vec<String> = body.lines().map(String::from).collect();
+            run_variants(&variants_for_day(day)?, &input_data)
+        }
+    };
+    let answers = check_agreement(&runs)?;
+
+    let timings: Vec<String> = runs
+        .iter()
+        .map(|run| {
+            format!(
+                "{{\"variant\":{},\"elapsed_ms\":{:.3}}}",
+                json_string(run.name),
+                run.elapsed.as_secs_f64() * 1000.
+            )
+        })
+        .collect();
+
+    Ok(format!(
+        "{{\"day\":{},\"answers\":{:?},\"timings\":[{}]}}",
+        day,
+        answers,
+        timings.join(",")
+    ))
+}
+
+/// Create a JSON body for an error message.
+fn error_json(message: &str) -> String {
+    format!("{{\"error\":{}}}", json_string(message))
+}
+
+/// Get the reason phrase for the status codes used by the service.
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handle_request() {
+        let (status, body) = handle_request("POST", "/solve/9", "2333133121414131402\n");
+        assert_eq!(200, status);
+        assert!(
+            body.starts_with(
+                "{\"day\":9,\"answers\":[1928, 2858],\"timings\":[{\"variant\":\"default\""
+            ),
+            "Unexpected response body {}",
+            body
+        );
+
+        let (status, _) = handle_request("POST", "/solve/19?variant=fast", "r, b\n\nrb\n");
+        assert_eq!(200, status);
+        let (status, body) = handle_request("POST", "/solve/1", "3 4\n4 3\n2 5\n");
+        assert_eq!(200, status);
+        assert!(body.contains("\"answers\":[3, 7]") && body.contains("\"variant\":\"stream\""));
+        assert_eq!(400, handle_request("POST", "/solve/19?variant=x", "").0);
+        assert_eq!(400, handle_request("POST", "/solve/abc", "").0);
+        assert_eq!(404, handle_request("POST", "/other", "").0);
+        assert_eq!(405, handle_request("GET", "/solve/1", "").0);
+    }
+
+    #[test]
+    fn test_respond_to_panic() {
+        assert_eq!((200, "{}".to_owned()), respond(|| Ok("{}".to_owned())));
+        assert_eq!(
+            (
+                500,
+                "{\"error\":\"The solution panicked on this input\"}".to_owned()
+            ),
+            respond(|| panic!("index out of bounds"))
+        );
+    }
+
+    #[test]
+    fn test_serve_over_tcp() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || serve(listener));
+
+        let body = "2333133121414131402";
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        write!(
+            stream,
+            "POST /solve/9 HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("\"answers\":[1928, 2858]"));
+    }
+}
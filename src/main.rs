@@ -1,56 +1,32 @@
-pub mod problems;
+#![cfg_attr(test, allow(clippy::vec_init_then_push))]
 
-use std::time::Instant;
+pub mod commands;
+pub mod problems;
 
 use problems::utils::*;
-use problems::*;
 
-fn main() {
-    let args = std::env::args().collect::<Vec<String>>();
-    let day = &args[1].parse::<i32>().unwrap();
-    let before = Instant::now();
-    println!("Running day {}", day);
-    match solve_day(*day) {
-        Ok(result) => {
-            println!("The answers are:");
-            for (i, val) in result.iter().enumerate() {
-                println!("{}: {}", i, val);
-            }
-            println!("Solution completed in {:2}ms", before.elapsed().as_millis())
-        }
-        Err(err) => println!("Failed with error: {}", err),
-    }
-}
+const USAGE: &str = "Usage: advent-of-code-24 <day> [--variant <name>] [--render [--colour]]
+                               [--export <dir> [--frames <n>] [--scale <n>]]
+                               [--explain [--json | --csv]]
+       advent-of-code-24 batch <day> <dir>
+       advent-of-code-24 bench <day> [--runs <n>] [--variant <name>]
+       advent-of-code-24 check <day> [<file>]
+       advent-of-code-24 serve [--port <port>]
+       advent-of-code-24 fetch <day> [--config <path>]";
 
-fn solve_day(day: i32) -> Result<Vec<u64>, String> {
-    let input_data =
-        read_input_for_day(day).or(Err(format!("Failed to read input data for day {}", day)))?;
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let result = match args.first().map(String::as_str) {
+        Some("batch") => commands::batch::run(&args[1..]),
+        Some("bench") => commands::bench::run(&args[1..]),
+        Some("check") => commands::check::run(&args[1..]),
+        Some("serve") => commands::serve::run(&args[1..]),
+        Some("fetch") => commands::fetch::run(&args[1..]),
+        Some(_) => commands::run::run(&args),
+        None => Err(USAGE.to_owned()),
+    };
 
-    match day {
-        1 => day1::solve(&input_data),
-        2 => day2::solve(&input_data),
-        3 => day3::solve(&input_data),
-        4 => day4::solve(&input_data),
-        5 => day5::solve(&input_data),
-        6 => day6::solve(&input_data),
-        7 => day7::solve(&input_data),
-        8 => day8::solve(&input_data),
-        9 => day9::solve(&input_data),
-        10 => day10::solve(&input_data),
-        11 => day11::solve(&input_data),
-        12 => day12::solve(&input_data),
-        13 => day13::solve(&input_data),
-        14 => day14::solve(&input_data),
-        15 => day15::solve(&input_data),
-        16 => day16::solve(&input_data),
-        17 => day17::solve(&input_data),
-        18 => day18::solve(&input_data),
-        19 => day19::solve(&input_data),
-        20 => day20::solve(&input_data),
-        ..=25 => Err(format!("Day {} is not solved yet", day)),
-        _ => Err(format!(
-            "Number {} is not a valid day for the calendar...",
-            day
-        )),
+    if let Err(err) = result {
+        println!("Failed with error: {}", err);
     }
 }
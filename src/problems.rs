@@ -18,4 +18,181 @@ pub mod day6;
 pub mod day7;
 pub mod day8;
 pub mod day9;
+pub mod geometry;
+pub mod image;
+pub mod input;
+pub mod math;
+pub mod render;
+pub mod report;
+pub mod simulation;
 pub mod utils;
+
+use std::io::BufRead;
+
+use input::{Diagnostic, PuzzleInput};
+
+/// Signature shared by the solvers for all days.
+pub type Solver = fn(&[String]) -> Result<Vec<u64>, String>;
+
+/// A named implementation of the solution for a day.
+#[derive(Clone, Copy)]
+pub struct Variant {
+    pub name: &'static str,
+    pub solve: Solver,
+}
+
+/// Signature of the hooks rendering the final state of a grid day.
+pub type Renderer = fn(&[String]) -> Result<render::Canvas, String>;
+
+/// Signature of the hooks exporting a simulation as a sequence of frames, up to a maximum count.
+pub type FrameExporter = fn(&[String], &mut image::FrameWriter, usize) -> Result<(), String>;
+
+/// Signature of the hooks explaining the answers of a day with a report of intermediate facts.
+pub type Explainer = fn(&[String]) -> Result<report::Report, String>;
+
+/// Signature of the hooks listing the structural problems in the input of a day.
+pub type Validator = fn(&[String]) -> Vec<Diagnostic>;
+
+/// Signature of the hooks solving a day while streaming the input from a reader, for days whose
+/// input can be too large to load as lines.
+pub type StreamSolver = fn(&mut dyn BufRead) -> Result<Vec<u64>, String>;
+
+/// Name of the variant for days with a single implementation.
+pub const DEFAULT_VARIANT: &str = "default";
+
+/// Solve the problem for the given day with the default variant, using the provided input data.
+pub fn solve_day(day: i32, input_data: &[String]) -> Result<Vec<u64>, String> {
+    (variants_for_day(day)?[0].solve)(input_data)
+}
+
+/// Find a variant for the day by name.
+pub fn find_variant(day: i32, name: &str) -> Result<Variant, String> {
+    let variants = variants_for_day(day)?;
+    variants
+        .iter()
+        .find(|variant| variant.name == name)
+        .copied()
+        .ok_or(format!(
+            "Day {} has no variant {}. Available variants are: {}",
+            day,
+            name,
+            variants
+                .iter()
+                .map(|variant| variant.name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+}
+
+/// Get all the registered variants for the day. The first one is the default.
+pub fn variants_for_day(day: i32) -> Result<Vec<Variant>, String> {
+    let solve: Solver = match day {
+        1 => return Ok(day1::VARIANTS.to_vec()),
+        2 => day2::solve,
+        3 => day3::solve,
+        4 => day4::solve,
+        5 => day5::solve,
+        6 => return Ok(day6::VARIANTS.to_vec()),
+        7 => day7::solve,
+        8 => day8::solve,
+        9 => day9::solve,
+        10 => day10::solve,
+        11 => day11::solve,
+        12 => day12::solve,
+        13 => day13::solve,
+        14 => day14::solve,
+        15 => return Ok(day15::VARIANTS.to_vec()),
+        16 => return Ok(day16::VARIANTS.to_vec()),
+        17 => day17::solve,
+        18 => return Ok(day18::VARIANTS.to_vec()),
+        19 => return Ok(day19::VARIANTS.to_vec()),
+        20 => return Ok(day20::VARIANTS.to_vec()),
+        ..=25 => return Err(format!("Day {} is not solved yet", day)),
+        _ => {
+            return Err(format!(
+                "Number {} is not a valid day for the calendar...",
+                day
+            ))
+        }
+    };
+
+    Ok(vec![Variant {
+        name: DEFAULT_VARIANT,
+        solve,
+    }])
+}
+
+/// Get the hook rendering the final state for the day, if it is a grid day.
+pub fn renderer_for_day(day: i32) -> Result<Renderer, String> {
+    match day {
+        4 => Ok(day4::render),
+        6 => Ok(day6::render),
+        10 => Ok(day10::render),
+        12 => Ok(day12::render),
+        14 => Ok(day14::render),
+        15 => Ok(day15::render),
+        16 => Ok(day16::render),
+        20 => Ok(day20::render),
+        _ => Err(format!("Day {} has no render hook", day)),
+    }
+}
+
+/// Get the hook exporting the frames of a simulation for the day, if it has one.
+pub fn frame_exporter_for_day(day: i32) -> Result<FrameExporter, String> {
+    match day {
+        14 => Ok(day14::export_frames),
+        15 => Ok(day15::export_frames),
+        _ => Err(format!("Day {} has no frame export hook", day)),
+    }
+}
+
+/// Get the hook explaining the answers for the day, if it has one.
+pub fn explainer_for_day(day: i32) -> Result<Explainer, String> {
+    match day {
+        1 => Ok(day1::explain),
+        2 => Ok(day2::explain),
+        3 => Ok(day3::explain),
+        5 => Ok(day5::explain),
+        12 => Ok(day12::explain),
+        13 => Ok(day13::explain),
+        20 => Ok(day20::explain),
+        _ => Err(format!("Day {} has no explain hook", day)),
+    }
+}
+
+/// Get the hook solving the day from a reader without loading the lines, if it has one.
+pub fn stream_solver_for_day(day: i32) -> Result<StreamSolver, String> {
+    match day {
+        1 => Ok(day1::solve_reader),
+        _ => Err(format!("Day {} has no stream hook", day)),
+    }
+}
+
+/// Get the hook validating the input for the day.
+pub fn validator_for_day(day: i32) -> Result<Validator, String> {
+    let validator: Validator = match day {
+        1 => day1::LocationLists::validate,
+        2 => day2::Reports::validate,
+        3 => day3::CorruptedMemory::validate,
+        4 => day4::WordSearch::validate,
+        5 => day5::PrintQueue::validate,
+        6 => day6::LabMap::validate,
+        7 => day7::Equations::validate,
+        8 => day8::AntennaMap::validate,
+        9 => day9::DiskMap::validate,
+        10 => day10::TopographicMap::validate,
+        11 => day11::Stones::validate,
+        12 => day12::Garden::validate,
+        13 => day13::Arcade::validate,
+        14 => day14::Robots::validate,
+        15 => day15::WarehousePlan::validate,
+        16 => day16::Maze::validate,
+        17 => day17::Computer::validate,
+        18 => day18::FallingBytes::validate,
+        19 => day19::Towels::validate,
+        20 => day20::RaceTrack::validate,
+        _ => return Err(format!("Day {} has no input validation", day)),
+    };
+
+    Ok(validator)
+}
@@ -1,50 +1,372 @@
+use std::{fmt, io::BufRead};
+
 use hashbrown::HashMap;
 
-use crate::parse_pair_from_str;
+use crate::sort_unsigned;
+
+use super::{
+    input::{check_lines, format_diagnostics, Diagnostic, PuzzleInput},
+    report::{Report, Section},
+    Variant,
+};
+
+/// Tally the values densely in a vec when its length is at most this factor times their number.
+const DENSE_TALLY_FACTOR: usize = 4;
+
+/// The available implementations for day 1.
+pub const VARIANTS: &[Variant] = &[
+    Variant {
+        name: "linear",
+        solve,
+    },
+    Variant {
+        name: "comparison",
+        solve: solve_comparison,
+    },
+];
+
+/// The two lists of location IDs, one per column of the input.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocationLists {
+    pub first: Vec<u32>,
+    pub second: Vec<u32>,
+}
+
+impl LocationLists {
+    /// Read the lists line by line from a reader, without keeping the lines in memory.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, String> {
+        LocationLists::from_lines(
+            reader.lines().enumerate().map(|(i, line)| {
+                line.map_err(|err| format!("Failed to read line {}: {}", i + 1, err))
+            }),
+        )
+    }
+
+    /// Parse the lines in a single pass, failing with the problems of every bad line.
+    fn from_lines<S, I>(lines: I) -> Result<Self, String>
+    where
+        S: AsRef<str>,
+        I: Iterator<Item = Result<S, String>>,
+    {
+        let mut lists = LocationLists {
+            first: Vec::with_capacity(lines.size_hint().0),
+            second: Vec::with_capacity(lines.size_hint().0),
+        };
+        let mut diagnostics = Vec::new();
+        for (i, line) in lines.enumerate() {
+            match parse_line(line?.as_ref()) {
+                Ok((first, second)) => {
+                    lists.first.push(first);
+                    lists.second.push(second);
+                }
+                Err(err) => diagnostics.push(Diagnostic::at(i, err)),
+            }
+        }
+
+        if !diagnostics.is_empty() {
+            return Err(format_diagnostics(&diagnostics));
+        }
+        Ok(lists)
+    }
+}
+
+impl PuzzleInput for LocationLists {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        LocationLists::from_lines(input_data.iter().map(Ok))
+    }
+
+    fn validate(input_data: &[String]) -> Vec<Diagnostic> {
+        check_lines(input_data, 0, parse_line)
+    }
+
+    /// Parsing already reports every bad line, so the lines are not validated separately.
+    fn parse_checked(input_data: &[String]) -> Result<Self, String> {
+        LocationLists::parse(input_data)
+    }
+}
+
+impl fmt::Display for LocationLists {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (first, second) in self.first.iter().zip(&self.second) {
+            writeln!(f, "{}   {}", first, second)?;
+        }
+        Ok(())
+    }
+}
 
 /// Solve the problem for day one, given the provided data.
 pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
-    let (mut first_list, mut second_list) = read_input_into_lists(input_data)?;
+    Ok(solve_lists(LocationLists::parse_checked(input_data)?))
+}
 
-    // Part 1
-    first_list.sort();
-    second_list.sort();
+/// Solve the problem for day one, streaming the data from a reader.
+pub fn solve_reader(reader: &mut dyn BufRead) -> Result<Vec<u64>, String> {
+    Ok(solve_lists(LocationLists::from_reader(reader)?))
+}
+
+/// Solve the problem for day one with comparison sorts and a hashed tally.
+pub fn solve_comparison(input_data: &[String]) -> Result<Vec<u64>, String> {
+    let LocationLists {
+        mut first,
+        mut second,
+    } = LocationLists::parse_checked(input_data)?;
+
+    first.sort_unstable();
+    second.sort_unstable();
 
-    let mut answer_part_1 = 0;
-    for (first, second) in first_list.iter().zip(&second_list) {
-        answer_part_1 += first.abs_diff(*second);
+    let mut matches: HashMap<u32, u32> = HashMap::with_capacity(first.len());
+    for &num in second.iter() {
+        *matches.entry(num).or_insert(0) += 1;
     }
 
+    Ok(vec![
+        total_distance(&first, &second),
+        similarity_score(&first, |num| *matches.get(&num).unwrap_or(&0)),
+    ])
+}
+
+/// Solve both parts in linear time, sorting without comparisons.
+fn solve_lists(lists: LocationLists) -> Vec<u64> {
+    let LocationLists {
+        mut first,
+        mut second,
+    } = lists;
+
+    // Part 1
+    sort_unsigned(&mut first);
+    sort_unsigned(&mut second);
+    let answer_part_1 = total_distance(&first, &second);
+
     // Part 2
-    let mut matches: HashMap<u32, u32> = HashMap::with_capacity(first_list.len());
-    for &num in second_list.iter() {
-        *matches.entry(num).or_insert(0) += 1;
+    let tally = Tally::new(&second);
+    let answer_part_2 = similarity_score(&first, |num| tally.count(num));
+
+    vec![answer_part_1, answer_part_2]
+}
+
+/// Explain the answers with the sorted pairings, the similarity of each distinct value of the
+/// first list and the values without a counterpart in the other list.
+pub fn explain(input_data: &[String]) -> Result<Report, String> {
+    let LocationLists {
+        mut first,
+        mut second,
+    } = LocationLists::parse_checked(input_data)?;
+    sort_unsigned(&mut first);
+    sort_unsigned(&mut second);
+
+    let mut pairings = Section::new("Pairings", &["Left", "Right", "Distance"]);
+    for (&left, &right) in first.iter().zip(&second) {
+        pairings.push_row(vec![left.into(), right.into(), left.abs_diff(right).into()]);
+    }
+
+    let first_counts = count_runs(&first);
+    let second_counts = count_runs(&second);
+    let mut similarity = Section::new(
+        "Similarity",
+        &["Value", "Left count", "Right count", "Contribution"],
+    );
+    let mut unmatched = Section::new("Unmatched values", &["List", "Value", "Count"]);
+    let (mut i, mut j) = (0, 0);
+    while i < first_counts.len() || j < second_counts.len() {
+        let left = first_counts.get(i).copied();
+        let right = second_counts.get(j).copied();
+        match (left, right) {
+            (Some((val, count)), Some((other, other_count))) if val == other => {
+                similarity.push_row(vec![
+                    val.into(),
+                    count.into(),
+                    other_count.into(),
+                    (val as u64 * count as u64 * other_count as u64).into(),
+                ]);
+                i += 1;
+                j += 1;
+            }
+            (Some((val, count)), right) if right.is_none_or(|(other, _)| val < other) => {
+                similarity.push_row(vec![val.into(), count.into(), 0.into(), 0.into()]);
+                unmatched.push_row(vec!["left".into(), val.into(), count.into()]);
+                i += 1;
+            }
+            (_, Some((other, other_count))) => {
+                unmatched.push_row(vec!["right".into(), other.into(), other_count.into()]);
+                j += 1;
+            }
+            (_, None) => unreachable!("The loop ends when both lists are exhausted"),
+        }
+    }
+
+    Ok(Report::new(vec![pairings, similarity, unmatched]))
+}
+
+/// Count the runs of equal values in a sorted list, as pairs of the value and its count.
+fn count_runs(sorted: &[u32]) -> Vec<(u32, u32)> {
+    let mut runs: Vec<(u32, u32)> = Vec::new();
+    for &val in sorted {
+        match runs.last_mut() {
+            Some((last, count)) if *last == val => *count += 1,
+            _ => runs.push((val, 1)),
+        }
     }
+    runs
+}
 
-    let mut answer_part_2 = 0;
-    for &num in first_list.iter() {
-        let num_matches = *matches.get(&num).unwrap_or(&0);
-        answer_part_2 += num * num_matches;
+/// Parse a line into the two location IDs, separated by any whitespace.
+fn parse_line(line: &str) -> Result<(u32, u32), String> {
+    let values = line
+        .split_whitespace()
+        .map(|s| {
+            s.parse::<u32>()
+                .map_err(|_| format!("Failed to parse a location ID from {}", s))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    match values[..] {
+        [first, second] => Ok((first, second)),
+        _ => Err(format!("Expected two values but found {}", values.len())),
     }
+}
 
-    Ok(vec![answer_part_1 as u64, answer_part_2 as u64])
+/// Sum of the distances between the sorted lists.
+fn total_distance(first: &[u32], second: &[u32]) -> u64 {
+    first
+        .iter()
+        .zip(second)
+        .map(|(a, b)| a.abs_diff(*b) as u64)
+        .sum()
 }
 
-// Parse the text data into two lists of u32s.
-fn read_input_into_lists(data: &[String]) -> Result<(Vec<u32>, Vec<u32>), String> {
-    let mut first_list: Vec<u32> = Vec::with_capacity(data.len());
-    let mut second_list: Vec<u32> = Vec::with_capacity(data.len());
+/// Sum of the values of the first list, times the number of times they appear in the second.
+fn similarity_score<F: Fn(u32) -> u32>(first: &[u32], count: F) -> u64 {
+    first
+        .iter()
+        .map(|&num| num as u64 * count(num) as u64)
+        .sum()
+}
+
+/// The number of times each value appears in a list.
+enum Tally {
+    /// Counts indexed by the value, for values in a small range.
+    Dense(Vec<u32>),
+    Sparse(HashMap<u32, u32>),
+}
 
-    for s in data.iter() {
-        let (first_val, second_val) = parse_pair_from_str::<u32>(s, "   ")?;
-        first_list.push(first_val);
-        second_list.push(second_val);
+impl Tally {
+    fn new(values: &[u32]) -> Tally {
+        let max = values.iter().max().map_or(0, |&max| max as usize);
+        if max < DENSE_TALLY_FACTOR * values.len() {
+            let mut counts = vec![0; max + 1];
+            for &val in values {
+                counts[val as usize] += 1;
+            }
+            Tally::Dense(counts)
+        } else {
+            let mut counts = HashMap::with_capacity(values.len());
+            for &val in values {
+                *counts.entry(val).or_insert(0) += 1;
+            }
+            Tally::Sparse(counts)
+        }
     }
 
-    Ok((first_list, second_list))
+    fn count(&self, val: u32) -> u32 {
+        match self {
+            Tally::Dense(counts) => counts.get(val as usize).copied().unwrap_or(0),
+            Tally::Sparse(counts) => counts.get(&val).copied().unwrap_or(0),
+        }
+    }
 }
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::problems::{
+        input::testing::{assert_text_round_trip, check_round_trips, Rng},
+        report::Value,
+    };
+
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<LocationLists>("3   4\n4   3\n2   5\n1   3\n3   9\n3   3");
+        check_round_trips(1, |rng| {
+            let len = rng.range(0, 20);
+            LocationLists {
+                first: (0..len).map(|_| rng.range(0, 100000) as u32).collect(),
+                second: (0..len).map(|_| rng.range(0, 100000) as u32).collect(),
+            }
+        });
+    }
+
+    #[test]
+    fn test_validate() {
+        let data: Vec<String> = "3   4\n4   x\n2".lines().map(String::from).collect();
+        assert_eq!(
+            vec![
+                Diagnostic::at(1, "Failed to parse a location ID from x".to_owned()),
+                Diagnostic::at(2, "Expected two values but found 1".to_owned()),
+            ],
+            LocationLists::validate(&data)
+        );
+    }
+
+    #[test]
+    fn test_example_data() {
+        let text = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3";
+        let data: Vec<String> = text.lines().map(String::from).collect();
+
+        let result = solve(&data).unwrap();
+        assert_eq!(vec![11, 31], result);
+        assert_eq!(result, solve_comparison(&data).unwrap());
+
+        // Any whitespace separates the values when streaming
+        let text = text.replace("   ", "\t ");
+        assert_eq!(result, solve_reader(&mut text.as_bytes()).unwrap());
+        assert_eq!(
+            Err("Found 2 problems in the input:\n  \
+                 line 2: Expected two values but found 3\n  \
+                 line 3: Expected two values but found 1"
+                .to_owned()),
+            solve_reader(&mut "3 4\n4 3 5\n6".as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_explain() {
+        let data: Vec<String> = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3"
+            .lines()
+            .map(String::from)
+            .collect();
+        let report = explain(&data).unwrap();
+        let [pairings, similarity, unmatched] = &report.sections[..] else {
+            panic!(
+                "Expected three sections but found {}",
+                report.sections.len()
+            );
+        };
+
+        assert_eq!(vec![Value::from(1), 3.into(), 2.into()], pairings.rows[0]);
+        assert_eq!(vec![Value::from(4), 9.into(), 5.into()], pairings.rows[5]);
+        let contributions: Vec<&Value> = similarity.rows.iter().map(|row| &row[3]).collect();
+        assert_eq!(
+            vec![&Value::from(0), &0.into(), &27.into(), &4.into()],
+            contributions
+        );
+        let unmatched: Vec<String> = unmatched
+            .rows
+            .iter()
+            .map(|row| format!("{} {}", row[0], row[1]))
+            .collect();
+        assert_eq!(vec!["left 1", "left 2", "right 5", "right 9"], unmatched);
+    }
+
+    #[test]
+    fn test_large_lists() {
+        let mut rng = Rng::new(41);
+        for range in [1000, 1 << 30] {
+            let data: Vec<String> = (0..5000)
+                .map(|_| format!("{} {}", rng.range(0, range), rng.range(0, range)))
+                .collect();
+            assert_eq!(solve_comparison(&data), solve(&data));
+        }
+    }
+
     #[test]
     #[cfg(feature = "real_inputs")]
     fn test_day_1() {
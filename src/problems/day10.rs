@@ -1,11 +1,52 @@
+use std::fmt;
+
 use hashbrown::HashSet;
 
 use crate::Vec2u;
 
-use super::utils;
+use super::{
+    input::{check_grid, grid_cells, grid_size, Diagnostic, PuzzleInput},
+    render::{Canvas, Highlight},
+    utils,
+};
+
+/// The topographic map, as rows of heights. Impassable cells are marked with `.`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TopographicMap {
+    pub rows: Vec<String>,
+}
+
+impl PuzzleInput for TopographicMap {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        grid_size(input_data)?;
+        if let Some((pos, c)) =
+            grid_cells(input_data).find(|&(_, c)| !c.is_ascii_digit() && c != '.')
+        {
+            return Err(format!("Invalid height {} at {:?}.", c, pos));
+        }
+
+        Ok(TopographicMap {
+            rows: input_data.to_vec(),
+        })
+    }
+
+    fn validate(input_data: &[String]) -> Vec<Diagnostic> {
+        check_grid(input_data, 0, |c| c.is_ascii_digit() || c == '.')
+    }
+}
+
+impl fmt::Display for TopographicMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in &self.rows {
+            writeln!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
 
 /// Solve the problem for day ten, given the provided data.
 pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
+    let input_data = &TopographicMap::parse_checked(input_data)?.rows;
     let trail_heads = find_trailheads(input_data);
 
     let mut options = 0;
@@ -22,6 +63,56 @@ pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
     Ok(vec![unique_options, options])
 }
 
+/// Render the map with only the cells on a complete trail, and the trailheads marked.
+pub fn render(input_data: &[String]) -> Result<Canvas, String> {
+    let input_data = &TopographicMap::parse_checked(input_data)?.rows;
+    let trail_heads = find_trailheads(input_data);
+
+    let mut on_trail = HashSet::new();
+    for &head in trail_heads.iter() {
+        trace_trails(0, head, &mut Vec::new(), &mut on_trail, input_data);
+    }
+
+    // Blank out the cells that are not part of any trail.
+    let mut canvas = Canvas::from_lines(input_data);
+    let (num_rows, num_cols) = canvas.size();
+    for i in 0..num_rows {
+        for j in 0..num_cols {
+            if !on_trail.contains(&(i, j)) {
+                canvas.set_char((i, j), '.');
+            }
+        }
+    }
+    canvas.overlay(on_trail, Highlight::Path);
+    canvas.overlay(trail_heads, Highlight::Marker);
+
+    Ok(canvas)
+}
+
+/// Walk all trails from the position, and collect the cells of every trail reaching the top.
+fn trace_trails(
+    level: usize,
+    pos: Vec2u,
+    trail: &mut Vec<Vec2u>,
+    on_trail: &mut HashSet<Vec2u>,
+    map: &[String],
+) {
+    trail.push(pos);
+    if level == 9 {
+        on_trail.extend(trail.iter().copied());
+    } else {
+        for (d_i, d_j) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            if let Some((i, j)) = utils::increment_2d_index(pos.0, pos.1, d_i, d_j, 1) {
+                if utils::get_char(map, i, j).and_then(|c| c.to_digit(10)) == Some(level as u32 + 1)
+                {
+                    trace_trails(level + 1, (i, j), trail, on_trail, map);
+                }
+            }
+        }
+    }
+    trail.pop();
+}
+
 /// Find all the trailheads in the map.
 fn find_trailheads(input_data: &[String]) -> Vec<Vec2u> {
     let mut trailheads = Vec::new();
@@ -71,8 +162,38 @@ fn find_valid_step(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::problems::input::testing::{assert_text_round_trip, check_round_trips};
     use std::str::FromStr;
 
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<TopographicMap>("...0...\n...1...\n6543456\n7.....7");
+        check_round_trips(10, |rng| {
+            let cols = rng.range(1, 12);
+            TopographicMap {
+                rows: (0..rng.range(1, 12))
+                    .map(|_| {
+                        (0..cols)
+                            .map(|_| rng.choose(&['0', '1', '5', '8', '9', '.']))
+                            .collect()
+                    })
+                    .collect(),
+            }
+        });
+    }
+
+    #[test]
+    fn test_validate() {
+        let data: Vec<String> = "0123\n1x34\n89".lines().map(String::from).collect();
+        assert_eq!(
+            vec![
+                Diagnostic::at(1, "Invalid character 'x' at column 2.".to_owned()),
+                Diagnostic::at(2, "Row has 2 cells, expected 4.".to_owned()),
+            ],
+            TopographicMap::validate(&data)
+        );
+    }
+
     #[test]
     fn test_example_data() {
         let mut data = Vec::new();
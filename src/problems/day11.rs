@@ -1,10 +1,53 @@
+use std::fmt;
+
 use hashbrown::HashMap;
 
-use super::utils;
+use super::{
+    input::{check_lines, Diagnostic, PuzzleInput},
+    math::split_digits,
+    utils,
+};
+
+/// The numbers engraved on the stones, in order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stones {
+    pub stones: Vec<u64>,
+}
+
+impl PuzzleInput for Stones {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        let line = input_data.first().ok_or("The input has no stones.")?;
+        Ok(Stones {
+            stones: utils::parse_sequence_from_str::<u64>(line, " ")?,
+        })
+    }
+
+    fn validate(input_data: &[String]) -> Vec<Diagnostic> {
+        if input_data.is_empty() {
+            return vec![Diagnostic::input("The input has no stones.".to_owned())];
+        }
+
+        let mut diagnostics = check_lines(&input_data[..1], 0, |line| {
+            utils::parse_sequence_from_str::<u64>(line, " ")
+        });
+        diagnostics.extend(
+            (1..input_data.len())
+                .map(|i| Diagnostic::at(i, "Expected all stones on the first line.".to_owned())),
+        );
+        diagnostics
+    }
+}
+
+impl fmt::Display for Stones {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let stones: Vec<String> = self.stones.iter().map(|val| val.to_string()).collect();
+        writeln!(f, "{}", stones.join(" "))
+    }
+}
 
 /// Solve the problem for day eleven, given the provided data.
 pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
-    let mut stone_map = parse_stone_map(&input_data[0])?;
+    let mut stone_map = count_stones(&Stones::parse_checked(input_data)?.stones);
 
     // Part 1
     for _ in 0..25 {
@@ -22,14 +65,13 @@ pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
 }
 
 /// Maps the initial stones into a map, with the count of each number
-fn parse_stone_map(input: &str) -> Result<HashMap<u64, u64>, String> {
-    let all_stones = utils::parse_sequence_from_str::<u64>(input, " ")?;
+fn count_stones(all_stones: &[u64]) -> HashMap<u64, u64> {
     let mut unique_stones = HashMap::with_capacity(all_stones.len());
-    for val in all_stones {
+    for &val in all_stones {
         *unique_stones.entry(val).or_insert(0) += 1;
     }
 
-    Ok(unique_stones)
+    unique_stones
 }
 
 /// Compute a new map from the stones in the input map, by applying the rules
@@ -39,7 +81,7 @@ fn map_stones(stone_map: &mut HashMap<u64, u64>) -> Result<(), String> {
     for (&val, &count) in stone_map.iter() {
         if val == 0 {
             *new_map.entry(1).or_insert(0) += count;
-        } else if let Some((a, b)) = split_val(val)? {
+        } else if let Some((a, b)) = split_digits(val) {
             *new_map.entry(a).or_insert(0) += count;
             *new_map.entry(b).or_insert(0) += count;
         } else {
@@ -51,31 +93,34 @@ fn map_stones(stone_map: &mut HashMap<u64, u64>) -> Result<(), String> {
     Ok(())
 }
 
-// Split a val with even number of digits into two numbers.
-fn split_val(val: u64) -> Result<Option<(u64, u64)>, String> {
-    let num_str = val.to_string();
-    let len = num_str.len();
-
-    if !len % 2 == 0 {
-        return Ok(None);
-    }
-
-    let (first_half, second_half) = num_str.split_at(len / 2);
-    let first_half_num = first_half
-        .parse::<u64>()
-        .map_err(|_| format!("Failed to parse {} into a number", first_half))?;
-    let second_half_num = second_half
-        .parse::<u64>()
-        .map_err(|_| format!("Failed to parse {} into a number", second_half))?;
-
-    Ok(Some((first_half_num, second_half_num)))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::problems::input::testing::{assert_text_round_trip, check_round_trips};
     use std::str::FromStr;
 
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<Stones>("125 17");
+        check_round_trips(11, |rng| Stones {
+            stones: (0..rng.range(1, 10))
+                .map(|_| rng.next_u64() % 100_000)
+                .collect(),
+        });
+    }
+
+    #[test]
+    fn test_validate() {
+        let data: Vec<String> = "125 x\n3".lines().map(String::from).collect();
+        assert_eq!(
+            vec![
+                Diagnostic::at(0, "Failed to parse u64 from x".to_owned()),
+                Diagnostic::at(1, "Expected all stones on the first line.".to_owned()),
+            ],
+            Stones::validate(&data)
+        );
+    }
+
     #[test]
     fn test_example_data() {
         let mut data = Vec::new();
@@ -1,25 +1,123 @@
 use hashbrown::{HashMap, HashSet};
-use std::collections::VecDeque;
+use std::{collections::VecDeque, fmt};
 
 use crate::{increment_2d_index, Vec2u};
 
-use super::utils;
+use super::{
+    input::{check_grid, grid_cells, grid_size, Diagnostic, PuzzleInput},
+    render::{Canvas, Highlight},
+    report::{Report, Section},
+    utils,
+};
 
 const NEIGHBOURS: &[(isize, isize)] = &[(-1, 0), (1, 0), (0, -1), (0, 1)];
 
+/// A continuous region of a single plant type, with the length and number of sides of its perimeter.
+struct Region {
+    cells: Vec<Vec2u>,
+    perimeter: u64,
+    sides: u64,
+}
+
+impl Region {
+    /// The area of the region.
+    fn area(&self) -> u64 {
+        self.cells.len() as u64
+    }
+}
+
+/// The garden map, as rows of plant types.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Garden {
+    pub rows: Vec<String>,
+}
+
+impl PuzzleInput for Garden {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        grid_size(input_data)?;
+        if let Some((pos, c)) = grid_cells(input_data).find(|&(_, c)| !c.is_ascii_uppercase()) {
+            return Err(format!("Invalid plant type {} at {:?}.", c, pos));
+        }
+
+        Ok(Garden {
+            rows: input_data.to_vec(),
+        })
+    }
+
+    fn validate(input_data: &[String]) -> Vec<Diagnostic> {
+        check_grid(input_data, 0, |c| c.is_ascii_uppercase())
+    }
+}
+
+impl fmt::Display for Garden {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in &self.rows {
+            writeln!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
+
 /// Solve the problem for day twelve, given the provided data.
 pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
-    let results = find_regions(input_data)?;
+    let results = find_regions(&Garden::parse_checked(input_data)?.rows)?;
 
-    let result_part_1 = results.iter().map(|(a, p, _)| a * p).sum();
-    let result_part_2 = results.iter().map(|(a, _, s)| a * s).sum();
+    let result_part_1 = results.iter().map(|r| r.area() * r.perimeter).sum();
+    let result_part_2 = results.iter().map(|r| r.area() * r.sides).sum();
 
     Ok(vec![result_part_1, result_part_2])
 }
 
+/// Render the map with each region in its own colour.
+pub fn render(input_data: &[String]) -> Result<Canvas, String> {
+    let input_data = &Garden::parse_checked(input_data)?.rows;
+    let regions = find_regions(input_data)?;
+
+    let mut canvas = Canvas::from_lines(input_data);
+    for (index, region) in regions.iter().enumerate() {
+        canvas.overlay(region.cells.iter().copied(), Highlight::Region(index));
+    }
+
+    Ok(canvas)
+}
+
+/// Report the area, perimeter and number of sides of each region, with its two prices.
+pub fn explain(input_data: &[String]) -> Result<Report, String> {
+    let input_data = &Garden::parse_checked(input_data)?.rows;
+    let regions = find_regions(input_data)?;
+
+    let mut section = Section::new(
+        "Regions",
+        &[
+            "Region",
+            "Plant",
+            "Area",
+            "Perimeter",
+            "Sides",
+            "Price",
+            "Bulk price",
+        ],
+    );
+    for (index, region) in regions.iter().enumerate() {
+        let (i, j) = region.cells[0];
+        let plant = utils::get_char(input_data, i, j).unwrap_or('?');
+        section.push_row(vec![
+            index.into(),
+            plant.to_string().into(),
+            region.area().into(),
+            region.perimeter.into(),
+            region.sides.into(),
+            (region.area() * region.perimeter).into(),
+            (region.area() * region.sides).into(),
+        ]);
+    }
+
+    Ok(Report::new(vec![section]))
+}
+
 /// Find all continuous regions using iterative, saturating bfs search.
-/// Returns a tuple with area and perimeter data of all found areas.
-fn find_regions(map: &[String]) -> Result<Vec<(u64, u64, u64)>, String> {
+/// Returns the cells and perimeter data of all found regions.
+fn find_regions(map: &[String]) -> Result<Vec<Region>, String> {
     let num_rows = map.len();
     let num_cols = map[0].len();
     let mut visited = HashSet::with_capacity(num_rows * num_cols);
@@ -39,13 +137,8 @@ fn find_regions(map: &[String]) -> Result<Vec<(u64, u64, u64)>, String> {
 }
 
 /// Compute a bfs search to track the continuous region with the certain char.
-fn bfs(
-    pos: Vec2u,
-    plant_type: char,
-    map: &[String],
-    visited: &mut HashSet<Vec2u>,
-) -> (u64, u64, u64) {
-    let mut area = 0;
+fn bfs(pos: Vec2u, plant_type: char, map: &[String], visited: &mut HashSet<Vec2u>) -> Region {
+    let mut cells = Vec::new();
     let mut perimeters = HashMap::new();
     let mut queue = VecDeque::new();
     queue.push_back(pos);
@@ -70,11 +163,15 @@ fn bfs(
                         .insert((d_i, d_j));
                 }
             }
-            area += 1;
+            cells.push(next);
         }
     }
-    let (perimeter_length, num_perimeter_sides) = analyze_perimeters(&mut perimeters);
-    (area, perimeter_length, num_perimeter_sides)
+    let (perimeter, sides) = analyze_perimeters(&mut perimeters);
+    Region {
+        cells,
+        perimeter,
+        sides,
+    }
 }
 
 /// Analyze the recorded perimeter to find the total length and the number of sides
@@ -128,8 +225,41 @@ fn trace_side(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::problems::{
+        input::testing::{assert_text_round_trip, check_round_trips},
+        report::Value,
+    };
     use std::str::FromStr;
 
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<Garden>("AAAA\nBBCD\nBBCC\nEEEC");
+        check_round_trips(12, |rng| {
+            let cols = rng.range(1, 12);
+            Garden {
+                rows: (0..rng.range(1, 12))
+                    .map(|_| {
+                        (0..cols)
+                            .map(|_| rng.choose(&['A', 'B', 'C', 'Z']))
+                            .collect()
+                    })
+                    .collect(),
+            }
+        });
+    }
+
+    #[test]
+    fn test_validate() {
+        let data: Vec<String> = "AAAA\nBBcD\nBB".lines().map(String::from).collect();
+        assert_eq!(
+            vec![
+                Diagnostic::at(1, "Invalid character 'c' at column 3.".to_owned()),
+                Diagnostic::at(2, "Row has 2 cells, expected 4.".to_owned()),
+            ],
+            Garden::validate(&data)
+        );
+    }
+
     #[test]
     fn test_example_data() {
         let mut data = Vec::new();
@@ -159,6 +289,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_explain() {
+        let data: Vec<String> = "AAAA\nBBCD\nBBCC\nEEEC".lines().map(String::from).collect();
+        let report = explain(&data).unwrap();
+        let rows = &report.sections[0].rows;
+
+        assert_eq!(5, rows.len());
+        assert_eq!(
+            vec![
+                Value::from(0),
+                "A".into(),
+                4.into(),
+                10.into(),
+                4.into(),
+                40.into(),
+                16.into()
+            ],
+            rows[0]
+        );
+        assert_eq!(
+            vec![
+                Value::from(2),
+                "C".into(),
+                4.into(),
+                10.into(),
+                8.into(),
+                40.into(),
+                32.into()
+            ],
+            rows[2]
+        );
+    }
+
     #[test]
     #[cfg(feature = "real_inputs")]
     fn test_day_12() {
@@ -1,61 +1,180 @@
+use std::fmt;
+
 use regex::Regex;
 
-type Vec2 = [f64; 2];
+use super::{
+    input::{sections, Diagnostic, PuzzleInput},
+    math::solve_2x2,
+    report::{Report, Section, Value},
+};
+
+type Vec2 = [i64; 2];
 type Matrix2 = [Vec2; 2];
 
-const OFFSET: f64 = 10000000000000.;
+const OFFSET: i64 = 10000000000000;
+
+/// The start of the three lines describing a claw machine.
+const MACHINE_PREFIXES: [&str; 3] = ["Button A:", "Button B:", "Prize:"];
+
+/// A claw machine, with the movement of both buttons and the location of the prize.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClawMachine {
+    pub button_a: Vec2,
+    pub button_b: Vec2,
+    pub prize: Vec2,
+}
+
+impl ClawMachine {
+    /// The machine as a 2x2 system of equations for the number of presses of each button.
+    fn equation(&self) -> (Matrix2, Vec2) {
+        (transpose_2x2(&[self.button_a, self.button_b]), self.prize)
+    }
+}
+
+/// All claw machines in the arcade.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Arcade {
+    pub machines: Vec<ClawMachine>,
+}
+
+impl PuzzleInput for Arcade {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        let mut machines = Vec::with_capacity(input_data.len() / 4);
+        let re = Regex::new(r"-?\d+").unwrap();
+        for data in input_data.split(|line| line.is_empty()) {
+            if data.len() == 3 {
+                machines.push(read_machine(data, &re)?);
+            }
+        }
+
+        Ok(Arcade { machines })
+    }
+
+    fn validate(input_data: &[String]) -> Vec<Diagnostic> {
+        let re = Regex::new(r"-?\d+").unwrap();
+        let mut diagnostics = Vec::new();
+        for (start, lines) in sections(input_data) {
+            if lines.is_empty() {
+                continue;
+            } else if lines.len() != MACHINE_PREFIXES.len() {
+                diagnostics.push(Diagnostic::at(
+                    start,
+                    format!(
+                        "Expected {} lines for a claw machine, but found {}.",
+                        MACHINE_PREFIXES.len(),
+                        lines.len()
+                    ),
+                ));
+                continue;
+            }
+
+            for (i, (line, prefix)) in lines.iter().zip(MACHINE_PREFIXES).enumerate() {
+                let Some(values) = line.strip_prefix(prefix) else {
+                    diagnostics.push(Diagnostic::at(
+                        start + i,
+                        format!("Expected the line to start with '{}'.", prefix),
+                    ));
+                    continue;
+                };
+                let count = re.find_iter(values).count();
+                if count != 2 {
+                    diagnostics.push(Diagnostic::at(
+                        start + i,
+                        format!("Expected 2 numbers, but found {}.", count),
+                    ));
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
+
+impl fmt::Display for Arcade {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, machine) in self.machines.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            let ClawMachine {
+                button_a,
+                button_b,
+                prize,
+            } = machine;
+            writeln!(f, "Button A: X+{}, Y+{}", button_a[0], button_a[1])?;
+            writeln!(f, "Button B: X+{}, Y+{}", button_b[0], button_b[1])?;
+            writeln!(f, "Prize: X={}, Y={}", prize[0], prize[1])?;
+        }
+        Ok(())
+    }
+}
 
 /// Solve the problem for day 13, given the provided data.
 pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
     // Parse equations
-    let equations = parse_equations(input_data)?;
+    let equations: Vec<(Matrix2, Vec2)> = Arcade::parse_checked(input_data)?
+        .machines
+        .iter()
+        .map(ClawMachine::equation)
+        .collect();
 
     let result_part_1 = equations
         .iter()
-        .map(|(mat, b)| solve_system(mat, b))
-        .filter(is_valid)
-        .map(|v| (3. * v[0] + v[1]))
-        .sum::<f64>()
-        .round() as u64;
+        .filter_map(|(mat, b)| solve_2x2(mat, b))
+        .map(|v| 3 * v[0] + v[1])
+        .sum::<i64>() as u64;
 
     let result_part_2 = equations
         .iter()
         .map(|(mat, b)| (mat, [OFFSET + b[0], OFFSET + b[1]]))
-        .map(|(mat, b)| solve_system(mat, &b))
-        .filter(is_valid)
-        .map(|v| (3. * v[0] + v[1]))
-        .sum::<f64>()
-        .round() as u64;
+        .filter_map(|(mat, b)| solve_2x2(mat, &b))
+        .map(|v| 3 * v[0] + v[1])
+        .sum::<i64>() as u64;
 
     Ok(vec![result_part_1, result_part_2])
 }
 
-/// Check if a solution is valid
-#[inline]
-fn is_valid(vec: &Vec2) -> bool {
-    (vec[0] % 1.).abs() < f64::EPSILON && (vec[1] % 1.).abs() < f64::EPSILON
-}
+/// Report the button presses and tokens for each machine, for the near and the far prize.
+pub fn explain(input_data: &[String]) -> Result<Report, String> {
+    let machines = Arcade::parse_checked(input_data)?.machines;
+
+    let mut section = Section::new(
+        "Machines",
+        &[
+            "Machine",
+            "A",
+            "B",
+            "Tokens",
+            "Far A",
+            "Far B",
+            "Far tokens",
+        ],
+    );
+    for (index, machine) in machines.iter().enumerate() {
+        let (mat, b) = machine.equation();
+        let near = solve_2x2(&mat, &b);
+        let far = solve_2x2(&mat, &[OFFSET + b[0], OFFSET + b[1]]);
 
-/// Parse the equations from the input data as 2x2 matrices with the constants and a solution vectors.
-fn parse_equations(input_data: &[String]) -> Result<Vec<(Matrix2, Vec2)>, String> {
-    let mut equations = Vec::with_capacity(input_data.len() / 4);
-    let re = Regex::new(r"-?\d+").unwrap();
-    for data in input_data.split(|line| line.is_empty()) {
-        if data.len() == 3 {
-            equations.push(read_equation_values(data, &re)?);
+        let mut row = vec![(index + 1).into()];
+        for presses in [near, far] {
+            match presses {
+                Some([a, b]) => row.extend([a.into(), b.into(), (3 * a + b).into()]),
+                None => row.extend([Value::Empty, Value::Empty, Value::Empty]),
+            }
         }
+        section.push_row(row);
     }
 
-    Ok(equations)
+    Ok(Report::new(vec![section]))
 }
 
-/// Parse the numbers from a string
-fn read_equation_values(lines: &[String], re: &Regex) -> Result<(Matrix2, Vec2), String> {
-    let values: Vec<Vec<f64>> = lines
+/// Parse the button movements and prize location of a machine from its three lines.
+fn read_machine(lines: &[String], re: &Regex) -> Result<ClawMachine, String> {
+    let values: Vec<Vec<i64>> = lines
         .iter()
         .map(|line| {
             re.find_iter(line)
-                .filter_map(|mat| mat.as_str().parse::<f64>().ok())
+                .filter_map(|mat| mat.as_str().parse::<i64>().ok())
                 .collect()
         })
         .collect();
@@ -64,15 +183,15 @@ fn read_equation_values(lines: &[String], re: &Regex) -> Result<(Matrix2, Vec2),
         return Err(format!("Failed to parse values from {:?}", lines));
     }
 
-    let r1 = vec2_from_slice(values[0].as_slice())?;
-    let r2 = vec2_from_slice(values[1].as_slice())?;
-    let b = vec2_from_slice(values[2].as_slice())?;
-
-    Ok((transpose_2x2(&[r1, r2]), b))
+    Ok(ClawMachine {
+        button_a: vec2_from_slice(values[0].as_slice())?,
+        button_b: vec2_from_slice(values[1].as_slice())?,
+        prize: vec2_from_slice(values[2].as_slice())?,
+    })
 }
 
 /// Crate rows with 2 values from a slice if possible.
-fn vec2_from_slice(vals: &[f64]) -> Result<Vec2, String> {
+fn vec2_from_slice(vals: &[i64]) -> Result<Vec2, String> {
     if vals.len() == 2 {
         Ok([vals[0], vals[1]])
     } else {
@@ -86,20 +205,48 @@ fn transpose_2x2(matrix: &Matrix2) -> Matrix2 {
     [[matrix[0][0], matrix[1][0]], [matrix[0][1], matrix[1][1]]]
 }
 
-/// Solve the equation Ax=b for a system with two variables using Cramer's rule.
-fn solve_system(mat: &Matrix2, b: &Vec2) -> Vec2 {
-    let det = mat[0][0] * mat[1][1] - mat[1][0] * mat[0][1];
-    [
-        (mat[1][1] * b[0] - b[1] * mat[0][1]) / det,
-        (mat[0][0] * b[1] - b[0] * mat[1][0]) / det,
-    ]
-}
-
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
     use super::*;
+    use crate::problems::input::testing::{assert_text_round_trip, check_round_trips};
+
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<Arcade>(
+            "Button A: X+94, Y+34\nButton B: X+22, Y+67\nPrize: X=8400, Y=5400\n\n\
+             Button A: X+26, Y+66\nButton B: X+67, Y+21\nPrize: X=12748, Y=12176",
+        );
+        check_round_trips(13, |rng| Arcade {
+            machines: (0..rng.range(0, 6))
+                .map(|_| ClawMachine {
+                    button_a: [rng.range(1, 100) as i64, rng.range(1, 100) as i64],
+                    button_b: [rng.range(1, 100) as i64, rng.range(1, 100) as i64],
+                    prize: [rng.range(0, 20000) as i64, rng.range(0, 20000) as i64],
+                })
+                .collect(),
+        });
+    }
+
+    #[test]
+    fn test_validate() {
+        let data: Vec<String> = "Button A: X+94, Y+34\nButton B: X+22, Y+67\n\n\
+                                 Button A: X+26, Y+66\nButton B: X+67, Y+21\nPrize: X=12748"
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            vec![
+                Diagnostic::at(
+                    0,
+                    "Expected 3 lines for a claw machine, but found 2.".to_owned()
+                ),
+                Diagnostic::at(5, "Expected 2 numbers, but found 1.".to_owned()),
+            ],
+            Arcade::validate(&data)
+        );
+    }
 
     #[test]
     fn test_example_data() {
@@ -131,6 +278,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_explain() {
+        let data: Vec<String> =
+            "Button A: X+94, Y+34\nButton B: X+22, Y+67\nPrize: X=8400, Y=5400\n\n\
+            Button A: X+26, Y+66\nButton B: X+67, Y+21\nPrize: X=12748, Y=12176"
+                .lines()
+                .map(String::from)
+                .collect();
+        let report = explain(&data).unwrap();
+        let rows = &report.sections[0].rows;
+
+        assert_eq!(2, rows.len());
+        assert_eq!(
+            vec![
+                Value::from(1),
+                80.into(),
+                40.into(),
+                280.into(),
+                Value::Empty,
+                Value::Empty,
+                Value::Empty
+            ],
+            rows[0]
+        );
+        assert_eq!(Value::Empty, rows[1][3]);
+        assert_eq!(Value::from(459236326669_i64), rows[1][6]);
+    }
+
     #[test]
     #[cfg(feature = "real_inputs")]
     fn test_day_13() {
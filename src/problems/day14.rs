@@ -1,58 +1,186 @@
+use std::fmt;
+
 use regex::Regex;
 
 use crate::{checked_add_signed_increment, Vec2i, Vec2u};
 
+use super::{
+    image::{FrameWriter, Image, ImageFormat, BLACK, WHITE},
+    input::{check_lines, Diagnostic, PuzzleInput},
+    math::crt,
+    render::Canvas,
+    simulation::{Simulation, SimulationRunner},
+};
+
+const MAP_SIZE: Vec2i = (101, 103);
+
+/// The robots in the bathroom, as (position, velocity).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Robots {
+    pub robots: Vec<(Vec2u, Vec2i)>,
+}
+
+impl PuzzleInput for Robots {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        let re = Regex::new(r"-?\d+").map_err(|_| "Failed to compile regex.")?;
+
+        let robots = input_data
+            .iter()
+            .enumerate()
+            .map(|(line_id, line)| {
+                parse_line(line, &re).map_err(|err| format!("Line {}: {}", line_id, err))
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Robots { robots })
+    }
+
+    fn validate(input_data: &[String]) -> Vec<Diagnostic> {
+        let re = Regex::new(r"-?\d+").unwrap();
+        check_lines(input_data, 0, |line| {
+            let (pos, _) = parse_line(line, &re)?;
+            if pos.0 as isize >= MAP_SIZE.0 || pos.1 as isize >= MAP_SIZE.1 {
+                return Err(format!(
+                    "Robot at {:?} is outside the {}x{} map.",
+                    pos, MAP_SIZE.0, MAP_SIZE.1
+                ));
+            }
+            Ok(())
+        })
+    }
+}
+
+impl fmt::Display for Robots {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for ((x, y), (v_x, v_y)) in &self.robots {
+            writeln!(f, "p={},{} v={},{}", x, y, v_x, v_y)?;
+        }
+        Ok(())
+    }
+}
+
 /// Solve the problem for day 14, given the provided data.
 pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
-    solve_for_map_size(input_data, (101, 103))
+    solve_for_map_size(input_data, MAP_SIZE)
+}
+
+/// Render the robots at the second found in part 2.
+pub fn render(input_data: &[String]) -> Result<Canvas, String> {
+    let robot_data = Robots::parse_checked(input_data)?.robots;
+    let second = find_most_clustered(&robot_data, MAP_SIZE)?;
+    let moved_robots = move_robots(&robot_data, MAP_SIZE, second)?;
+
+    let mut canvas = Canvas::new((MAP_SIZE.1 as usize, MAP_SIZE.0 as usize), '.');
+    for ((x, y), _) in moved_robots {
+        canvas.set_char((y, x), '#');
+    }
+
+    Ok(canvas)
+}
+
+/// Export the robot positions for each second as a sequence of black and white frames.
+pub fn export_frames(
+    input_data: &[String],
+    writer: &mut FrameWriter,
+    max_frames: usize,
+) -> Result<(), String> {
+    let robot_data = Robots::parse_checked(input_data)?.robots;
+    let motion = RobotMotion {
+        robots: robot_data,
+        map_size: MAP_SIZE,
+    };
+    let mut runner = SimulationRunner::new(motion);
+    for _ in 0..max_frames {
+        let mut image = Image::new(MAP_SIZE.0 as usize, MAP_SIZE.1 as usize, WHITE);
+        for (x, y) in runner.simulation().snapshot() {
+            image.set(x, y, BLACK);
+        }
+        writer.write(&image, ImageFormat::Pbm)?;
+        runner.step()?;
+    }
+
+    Ok(())
+}
+
+/// The robots moving around the map, one second per step.
+#[derive(Clone)]
+struct RobotMotion {
+    robots: Vec<(Vec2u, Vec2i)>,
+    map_size: Vec2i,
+}
+
+impl Simulation for RobotMotion {
+    type Snapshot = Vec<Vec2u>;
+
+    fn step(&mut self) -> Result<(), String> {
+        self.robots = move_robots(&self.robots, self.map_size, 1)?;
+        Ok(())
+    }
+
+    fn snapshot(&self) -> Vec<Vec2u> {
+        self.robots.iter().map(|&(pos, _)| pos).collect()
+    }
+
+    fn is_terminal(&self) -> bool {
+        false
+    }
 }
 
 /// Solve for a certain set of robots and size of the map.
 fn solve_for_map_size(input_data: &[String], map_size: Vec2i) -> Result<Vec<u64>, String> {
-    let robot_data = parse_robot_data(input_data)?;
+    let robot_data = Robots::parse_checked(input_data)?.robots;
 
     // Part 1
     let moved_robots = move_robots(&robot_data, map_size, 100)?;
     let result_part_1 = count_quadrants(&moved_robots, map_size.0 as usize, map_size.1 as usize);
 
     // Part 2
-    let mut min_sd = f64::MAX;
-    let mut min_i = 0;
-    for i in 0..10000 {
-        let update = move_robots(&robot_data, map_size, i)?;
-        let sd = std_dev(&update);
-        if sd < min_sd {
-            min_sd = sd;
-            min_i = i;
-        }
-    }
+    let min_i = find_most_clustered(&robot_data, map_size)?;
 
     Ok(vec![result_part_1, min_i as u64])
 }
 
-/// Compute the 2D standard deviation about the mean position of the data.
-fn std_dev(robot_data: &[(Vec2u, Vec2i)]) -> f64 {
-    let num_coords = robot_data.len() as f64;
+/// Find the second where the robots are the most tightly clustered.
+/// The x and y positions repeat with the width and height of the map, so the most clustered
+/// second is found for each axis separately, and combined with the Chinese remainder theorem.
+fn find_most_clustered(robot_data: &[(Vec2u, Vec2i)], map_size: Vec2i) -> Result<usize, String> {
+    if robot_data.is_empty() {
+        return Err("There are no robots to cluster.".to_owned());
+    }
 
-    let (x_sum, y_sum) = robot_data
-        .iter()
-        .fold((0.0, 0.0), |(x_acc, y_acc), (pos, _)| {
-            (x_acc + pos.0 as f64, y_acc + pos.1 as f64)
-        });
-    let mean = (x_sum / num_coords, y_sum / num_coords);
+    let periods = [map_size.0 as usize, map_size.1 as usize];
+    let mut min_variances = [(f64::MAX, 0); 2];
+    for second in 0..periods[0].max(periods[1]) {
+        let moved_robots = move_robots(robot_data, map_size, second)?;
+        let variances = [
+            variance(moved_robots.iter().map(|(pos, _)| pos.0)),
+            variance(moved_robots.iter().map(|(pos, _)| pos.1)),
+        ];
+        for axis in 0..2 {
+            if second < periods[axis] && variances[axis] < min_variances[axis].0 {
+                min_variances[axis] = (variances[axis], second);
+            }
+        }
+    }
 
-    let sum_squared_distances = robot_data
-        .iter()
-        .map(|(pos, _)| {
-            let x = pos.0 as f64;
-            let y = pos.1 as f64;
-            let dx = x - mean.0;
-            let dy = y - mean.1;
-            dx.powi(2) + dy.powi(2)
-        })
-        .sum::<f64>();
+    let (second, _) = crt(&[
+        (min_variances[0].1 as i64, map_size.0 as i64),
+        (min_variances[1].1 as i64, map_size.1 as i64),
+    ])
+    .ok_or(format!(
+        "The map size {:?} has no single period for the robot positions.",
+        map_size
+    ))?;
 
-    (sum_squared_distances / num_coords).sqrt()
+    Ok(second as usize)
+}
+
+/// Compute the variance of the values about their mean.
+fn variance<I: Iterator<Item = usize> + Clone>(values: I) -> f64 {
+    let count = values.clone().count() as f64;
+    let mean = values.clone().sum::<usize>() as f64 / count;
+
+    values.map(|val| (val as f64 - mean).powi(2)).sum::<f64>() / count
 }
 
 /// Count the number in each quadrant and multiply the results
@@ -76,19 +204,8 @@ fn count_quadrants(robot_data: &[(Vec2u, Vec2i)], w: usize, h: usize) -> u64 {
     q1 * q2 * q3 * q4
 }
 
-/// Parse the data for each robot, as (position, velocity).
-fn parse_robot_data(input_data: &[String]) -> Result<Vec<(Vec2u, Vec2i)>, String> {
-    let re = Regex::new(r"-?\d+").map_err(|_| "Failed to compile regex.")?;
-
-    input_data
-        .iter()
-        .enumerate()
-        .map(|(line_id, line)| parse_line(line, line_id, &re))
-        .collect()
-}
-
 /// Parse the values from a lines into a position and a velicity tuple.
-fn parse_line(line: &str, line_id: usize, re: &Regex) -> Result<(Vec2u, Vec2i), String> {
+fn parse_line(line: &str, re: &Regex) -> Result<(Vec2u, Vec2i), String> {
     let nums: Vec<isize> = re
         .find_iter(line)
         .filter_map(|mat| mat.as_str().parse::<isize>().ok())
@@ -96,8 +213,7 @@ fn parse_line(line: &str, line_id: usize, re: &Regex) -> Result<(Vec2u, Vec2i),
 
     if nums.len() != 4 {
         return Err(format!(
-            "Line {}: Expected 4 numbers, but found {}. Line content: '{}'",
-            line_id,
+            "Expected 4 numbers, but found {}. Line content: '{}'",
             nums.len(),
             line
         ));
@@ -106,10 +222,10 @@ fn parse_line(line: &str, line_id: usize, re: &Regex) -> Result<(Vec2u, Vec2i),
     let pos = (
         nums[0]
             .try_into()
-            .map_err(|_| format!("Line {}: Position x must be non-negative.", line_id))?,
+            .map_err(|_| "Position x must be non-negative.".to_owned())?,
         nums[1]
             .try_into()
-            .map_err(|_| format!("Line {}: Position y must be non-negative.", line_id))?,
+            .map_err(|_| "Position y must be non-negative.".to_owned())?,
     );
 
     Ok((pos, (nums[2], nums[3])))
@@ -148,8 +264,39 @@ fn move_robots(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::problems::input::testing::{assert_text_round_trip, check_round_trips};
     use std::str::FromStr;
 
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<Robots>("p=0,4 v=3,-3\np=6,3 v=-1,-3\np=10,3 v=-1,2");
+        check_round_trips(14, |rng| Robots {
+            robots: (0..rng.range(0, 12))
+                .map(|_| {
+                    let pos = (rng.range(0, 101), rng.range(0, 103));
+                    let vel = (
+                        rng.range(0, 201) as isize - 100,
+                        rng.range(0, 201) as isize - 100,
+                    );
+                    (pos, vel)
+                })
+                .collect(),
+        });
+    }
+
+    #[test]
+    fn test_validate() {
+        let data: Vec<String> = "p=0,4 v=3,-3\np=101,3 v=-1,-3\np=10,3 v=-1"
+            .lines()
+            .map(String::from)
+            .collect();
+        let lines: Vec<Option<usize>> = Robots::validate(&data)
+            .iter()
+            .map(|diagnostic| diagnostic.line)
+            .collect();
+        assert_eq!(vec![Some(2), Some(3)], lines);
+    }
+
     #[test]
     fn test_example_data() {
         let mut data = Vec::new();
@@ -174,6 +321,33 @@ mod tests {
             "Result for part 1 example should be 12 but was {}",
             result[0]
         );
+
+        // The positions repeat with a period of the product of the map dimensions
+        let motion = RobotMotion {
+            robots: Robots::parse(&data).unwrap().robots,
+            map_size: (11, 7),
+        };
+        let cycle = crate::problems::simulation::detect_cycle_brent(&motion, None).unwrap();
+        assert_eq!(
+            Some(crate::problems::simulation::Cycle {
+                start: 0,
+                length: 77
+            }),
+            cycle
+        );
+    }
+
+    #[test]
+    fn test_find_most_clustered() {
+        // All robots meet in a single cell after 23 seconds on a map with coprime sides
+        let map_size = (5, 7);
+        let velocities = [(1, 2), (-2, 1), (3, -3), (4, 5), (-1, -2), (2, 3)];
+        let clustered: Vec<(Vec2u, Vec2i)> = velocities.iter().map(|&v| ((2, 3), v)).collect();
+        // Moving on for the rest of the period of 35 seconds gives the positions at second 0
+        let robots = move_robots(&clustered, map_size, 35 - 23).unwrap();
+        assert_eq!(Ok(23), find_most_clustered(&robots, map_size));
+
+        assert!(find_most_clustered(&[], map_size).is_err());
     }
 
     #[test]
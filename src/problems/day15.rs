@@ -1,27 +1,190 @@
+use std::fmt;
+
 use hashbrown::HashSet;
 
-use crate::{increment_2d_index, Vec2i, Vec2u};
+use crate::{increment_2d_index, BitGrid, PositionSet, Vec2i, Vec2u};
+
+use super::{
+    image::{FrameWriter, Image, ImageFormat, MARKER, PATH, WALL, WHITE},
+    input::{
+        check_grid, check_lines, check_unique, grid_cells, grid_size, sections, write_grid,
+        Diagnostic, PuzzleInput,
+    },
+    render::{Canvas, Highlight},
+    simulation::{Simulation, SimulationRunner},
+    Variant,
+};
+
+/// The walls and the boxes in the warehouse.
+type MapData<W = HashSet<Vec2u>> = (W, HashSet<Vec2u>);
+
+/// The available implementations for day 15.
+pub const VARIANTS: &[Variant] = &[
+    Variant {
+        name: "hashed",
+        solve,
+    },
+    Variant {
+        name: "bitgrid",
+        solve: solve_bitgrid,
+    },
+];
+
+/// The initial layout of the warehouse, and the lines of movement commands for the robot.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WarehousePlan {
+    pub size: Vec2u,
+    pub walls: HashSet<Vec2u>,
+    pub boxes: HashSet<Vec2u>,
+    pub robot: Vec2u,
+    pub commands: Vec<String>,
+}
 
-type MapData = (HashSet<Vec2u>, HashSet<Vec2u>);
+impl WarehousePlan {
+    /// Split the plan into the map data, the start of the robot and the commands.
+    fn into_parts(self) -> (MapData, Vec2u, Vec<String>) {
+        ((self.walls, self.boxes), self.robot, self.commands)
+    }
+}
+
+impl PuzzleInput for WarehousePlan {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        let mut split = input_data.split(|line| line.is_empty());
+        let map = split
+            .next()
+            .ok_or("Failed to parse map from input".to_owned())?;
+        let commands = split
+            .next()
+            .ok_or("Failed to parse commands from input".to_owned())?;
+
+        let size = grid_size(map)?;
+        let mut walls = HashSet::new();
+        let mut boxes = HashSet::new();
+        let mut robot = None;
+        for (pos, ch) in grid_cells(map) {
+            match ch {
+                '#' => {
+                    walls.insert(pos);
+                }
+                'O' => {
+                    boxes.insert(pos);
+                }
+                '@' => {
+                    robot.get_or_insert(pos);
+                }
+                _ => (),
+            }
+        }
+
+        for command in commands.iter().flat_map(|line| line.chars()) {
+            step_from_command(&command)?;
+        }
+
+        Ok(WarehousePlan {
+            size,
+            walls,
+            boxes,
+            robot: robot.ok_or("No robot found in the map.")?,
+            commands: commands.to_vec(),
+        })
+    }
+
+    fn validate(input_data: &[String]) -> Vec<Diagnostic> {
+        let sections = sections(input_data);
+        let (_, map) = sections[0];
+        let mut diagnostics = check_grid(map, 0, |c| "#.O@".contains(c));
+        diagnostics.extend(check_unique(map, 0, &['@'], "robot"));
+
+        let Some(&(start, commands)) = sections.get(1) else {
+            diagnostics.push(Diagnostic::input(
+                "Missing the movement commands after the map.".to_owned(),
+            ));
+            return diagnostics;
+        };
+        diagnostics.extend(check_lines(commands, start, |line| {
+            for (j, command) in line.chars().enumerate() {
+                step_from_command(&command)
+                    .map_err(|err| format!("{} at column {}.", err, j + 1))?;
+            }
+            Ok(())
+        }));
+        if let Some(&(start, _)) = sections.get(2) {
+            diagnostics.push(Diagnostic::at(
+                start - 1,
+                "Expected only the map and the commands, separated by one empty line.".to_owned(),
+            ));
+        }
+
+        diagnostics
+    }
+}
+
+impl fmt::Display for WarehousePlan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_grid(f, self.size, |pos| {
+            if pos == self.robot {
+                '@'
+            } else if self.walls.contains(&pos) {
+                '#'
+            } else if self.boxes.contains(&pos) {
+                'O'
+            } else {
+                '.'
+            }
+        })?;
+        writeln!(f)?;
+        for line in &self.commands {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
 
 /// Solve the problem for day 15, given the provided data.
 pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
-    let (mut map_data, start, commands) = parse_input(input_data)?;
+    solve_with_walls(input_data, |walls| walls)
+}
+
+/// Solve the problem for day 15, with the walls stored in a bit grid.
+pub fn solve_bitgrid(input_data: &[String]) -> Result<Vec<u64>, String> {
+    solve_with_walls(input_data, |walls| {
+        let num_rows = walls.iter().map(|pos| pos.0 + 1).max().unwrap_or(0);
+        let num_cols = walls.iter().map(|pos| pos.1 + 1).max().unwrap_or(0);
+        BitGrid::from_positions((num_rows, num_cols), walls)
+    })
+}
 
-    let mut expanded = expand_map_data(&map_data);
+/// Solve both parts, converting the parsed walls to another representation.
+fn solve_with_walls<W: PositionSet, F: Fn(HashSet<Vec2u>) -> W>(
+    input_data: &[String],
+    convert_walls: F,
+) -> Result<Vec<u64>, String> {
+    let (map_data, start, commands) = WarehousePlan::parse_checked(input_data)?.into_parts();
+    let commands = commands.as_slice();
+
+    let (expanded_walls, expanded_boxes) = expand_map_data(&map_data);
+    let expanded = (convert_walls(expanded_walls), expanded_boxes);
+    let map_data = (convert_walls(map_data.0), map_data.1);
     // Part 1
-    execute_commands(&mut map_data, start, commands, false)?;
+    let warehouse = execute_commands(Warehouse::new(map_data, start, commands, false))?;
 
-    let result_part_1 = map_data
+    let result_part_1 = warehouse
+        .map_data
         .1
         .iter()
         .map(|pos| 100 * pos.0 + pos.1)
         .sum::<usize>() as u64;
 
     // Part 2
-    execute_commands(&mut expanded, (start.0, start.1 * 2), commands, true)?;
+    let warehouse = execute_commands(Warehouse::new(
+        expanded,
+        (start.0, start.1 * 2),
+        commands,
+        true,
+    ))?;
 
-    let result_part_2 = expanded
+    let result_part_2 = warehouse
+        .map_data
         .1
         .iter()
         .map(|pos| 100 * pos.0 + pos.1)
@@ -30,6 +193,110 @@ pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
     Ok(vec![result_part_1, result_part_2])
 }
 
+/// Render the warehouse after all commands of part 1, with the robot marked.
+pub fn render(input_data: &[String]) -> Result<Canvas, String> {
+    let (map_data, start, commands) = WarehousePlan::parse_checked(input_data)?.into_parts();
+    let warehouse = execute_commands(Warehouse::new(map_data, start, &commands, false))?;
+
+    let (walls, boxes) = &warehouse.map_data;
+    let num_rows = walls.iter().map(|pos| pos.0 + 1).max().unwrap_or(0);
+    let num_cols = walls.iter().map(|pos| pos.1 + 1).max().unwrap_or(0);
+    let mut canvas = Canvas::new((num_rows, num_cols), '.');
+    for &pos in walls.iter() {
+        canvas.set_char(pos, '#');
+    }
+    for &pos in boxes.iter() {
+        canvas.set_char(pos, 'O');
+    }
+    canvas.mark(warehouse.robot, '@');
+    canvas.overlay(boxes.iter().copied(), Highlight::Path);
+
+    Ok(canvas)
+}
+
+/// Export the expanded warehouse of part 2 after each command as a sequence of frames.
+pub fn export_frames(
+    input_data: &[String],
+    writer: &mut FrameWriter,
+    max_frames: usize,
+) -> Result<(), String> {
+    let (map_data, start, commands) = WarehousePlan::parse_checked(input_data)?.into_parts();
+    let expanded = expand_map_data(&map_data);
+    let warehouse = Warehouse::new(expanded, (start.0, start.1 * 2), &commands, true);
+
+    let mut runner = SimulationRunner::new(warehouse).with_step_limit(max_frames.saturating_sub(1));
+    let frame = |warehouse: &Warehouse| expanded_frame(&warehouse.map_data, warehouse.robot);
+    writer.write(&frame(runner.simulation()), ImageFormat::Ppm)?;
+    while runner.step()? {
+        writer.write(&frame(runner.simulation()), ImageFormat::Ppm)?;
+    }
+
+    Ok(())
+}
+
+/// The warehouse with the robot executing its commands, one command per step.
+struct Warehouse<W = HashSet<Vec2u>> {
+    map_data: MapData<W>,
+    robot: Vec2u,
+    commands: Vec<char>,
+    next_command: usize,
+    expanded: bool,
+}
+
+impl<W> Warehouse<W> {
+    /// Create the warehouse with the robot at the start, before executing any command.
+    fn new(map_data: MapData<W>, start: Vec2u, commands: &[String], expanded: bool) -> Self {
+        Warehouse {
+            map_data,
+            robot: start,
+            commands: commands.iter().flat_map(|line| line.chars()).collect(),
+            next_command: 0,
+            expanded,
+        }
+    }
+}
+
+impl<W: PositionSet> Simulation for Warehouse<W> {
+    /// The robot position and the sorted box positions.
+    type Snapshot = (Vec2u, Vec<Vec2u>);
+
+    fn step(&mut self) -> Result<(), String> {
+        let command = self.commands[self.next_command];
+        execute_command(&mut self.map_data, &mut self.robot, &command, self.expanded)?;
+        self.next_command += 1;
+        Ok(())
+    }
+
+    fn snapshot(&self) -> Self::Snapshot {
+        let mut boxes: Vec<Vec2u> = self.map_data.1.iter().copied().collect();
+        boxes.sort_unstable();
+        (self.robot, boxes)
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.next_command >= self.commands.len()
+    }
+}
+
+/// Draw the expanded warehouse, where each wall and box is two cells wide.
+fn expanded_frame(map_data: &MapData, robot: Vec2u) -> Image {
+    let (walls, boxes) = map_data;
+    let height = walls.iter().map(|pos| pos.0 + 1).max().unwrap_or(0);
+    let width = walls.iter().map(|pos| pos.1 + 2).max().unwrap_or(0);
+    let mut image = Image::new(width, height, WHITE);
+    for &(i, j) in walls.iter() {
+        image.set(j, i, WALL);
+        image.set(j + 1, i, WALL);
+    }
+    for &(i, j) in boxes.iter() {
+        image.set(j, i, PATH);
+        image.set(j + 1, i, PATH);
+    }
+    image.set(robot.1, robot.0, MARKER);
+
+    image
+}
+
 fn expand_map_data(map_data: &MapData) -> MapData {
     let expanded_walls = map_data.0.iter().map(|&pos| (pos.0, 2 * pos.1)).collect();
     let expanded_boxes = map_data.1.iter().map(|&pos| (pos.0, 2 * pos.1)).collect();
@@ -37,26 +304,17 @@ fn expand_map_data(map_data: &MapData) -> MapData {
     (expanded_walls, expanded_boxes)
 }
 
-/// Execute a sequence of commands and move the boxes.
-fn execute_commands(
-    map_data: &mut MapData,
-    start: Vec2u,
-    commands: &[String],
-    expanded: bool,
-) -> Result<(), String> {
-    let mut pos = start;
-    for sequence in commands {
-        for command in sequence.chars() {
-            execute_command(map_data, &mut pos, &command, expanded)?;
-        }
-    }
+/// Execute all commands of the warehouse and return the final state.
+fn execute_commands<W: PositionSet>(warehouse: Warehouse<W>) -> Result<Warehouse<W>, String> {
+    let mut runner = SimulationRunner::new(warehouse);
+    runner.run()?;
 
-    Ok(())
+    Ok(runner.into_inner())
 }
 
 /// Execute a command, and update the current position and boxes if applicable.
-fn execute_command(
-    map_data: &mut MapData,
+fn execute_command<W: PositionSet>(
+    map_data: &mut MapData<W>,
     pos: &mut Vec2u,
     command: &char,
     expanded: bool,
@@ -97,9 +355,9 @@ fn update_boxes(boxes: &mut HashSet<Vec2u>, to_shift: &HashSet<Vec2u>, step: &Ve
 }
 
 /// Move box to next position if possible
-fn can_move_box(
+fn can_move_box<W: PositionSet>(
     boxes: &mut HashSet<Vec2u>,
-    walls: &HashSet<Vec2u>,
+    walls: &W,
     pos: &Vec2u,
     step: &Vec2i,
     to_shift: &mut HashSet<Vec2u>,
@@ -122,7 +380,7 @@ fn can_move_box(
 }
 
 /// Check if a certain location contains a wall char.
-fn hits_wall(walls: &HashSet<Vec2u>, pos: &Vec2u, expanded: bool) -> bool {
+fn hits_wall<W: PositionSet>(walls: &W, pos: &Vec2u, expanded: bool) -> bool {
     walls.contains(pos) || (expanded && walls.contains(&(pos.0, pos.1 - 1)))
 }
 
@@ -186,52 +444,68 @@ fn step_from_command(command: &char) -> Result<Vec2i, String> {
     }
 }
 
-/// Parse the input data
-fn parse_input(input_data: &[String]) -> Result<(MapData, Vec2u, &[String]), String> {
-    let mut split = input_data.split(|line| line.is_empty());
-
-    let (map_data, start) = parse_input_map(
-        split
-            .next()
-            .ok_or("Failed to parse map from input".to_owned())?,
-    );
-
-    let commands = split
-        .next()
-        .ok_or("Failed to parse map from input".to_owned())?;
-
-    Ok((map_data, start, commands))
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::input::testing::{assert_text_round_trip, check_round_trips};
+    use std::str::FromStr;
 
-/// Parse the locations of all boxes and walls into two hash sets (walls, boxes).
-fn parse_input_map(map: &[String]) -> (MapData, Vec2u) {
-    let mut wall_pos = HashSet::new();
-    let mut box_pos = HashSet::new();
-    let mut start = (1, 1);
-    for (i, line) in map.iter().enumerate() {
-        for (j, ch) in line.chars().enumerate() {
-            match ch {
-                '#' => {
-                    wall_pos.insert((i, j));
-                }
-                'O' => {
-                    box_pos.insert((i, j));
-                }
-                '@' => {
-                    start = (i, j);
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<WarehousePlan>(
+            "########\n#..O.O.#\n##@.O..#\n#......#\n########\n\n<^^>>>vv\n<v>>v<<",
+        );
+        check_round_trips(15, |rng| {
+            let size = (rng.range(3, 10), rng.range(3, 10));
+            let robot = (rng.range(1, size.0 - 1), rng.range(1, size.1 - 1));
+            let mut walls = HashSet::new();
+            let mut boxes = HashSet::new();
+            for i in 0..size.0 {
+                for j in 0..size.1 {
+                    if i == 0 || j == 0 || i == size.0 - 1 || j == size.1 - 1 || rng.chance(0.1) {
+                        walls.insert((i, j));
+                    } else if rng.chance(0.2) {
+                        boxes.insert((i, j));
+                    }
                 }
-                _ => (),
             }
-        }
+            walls.remove(&robot);
+            boxes.remove(&robot);
+            let commands = (0..rng.range(1, 4))
+                .map(|_| {
+                    (0..rng.range(1, 20))
+                        .map(|_| rng.choose(&['<', '^', 'v', '>']))
+                        .collect()
+                })
+                .collect();
+            WarehousePlan {
+                size,
+                walls,
+                boxes,
+                robot,
+                commands,
+            }
+        });
     }
 
-    ((wall_pos, box_pos), start)
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::str::FromStr;
+    #[test]
+    fn test_validate() {
+        let data: Vec<String> = "#####\n#@.O#\n#.@x#\n#####\n\n<^>x\nv"
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            vec![
+                Diagnostic::at(2, "Invalid character 'x' at column 4.".to_owned()),
+                Diagnostic::at(
+                    2,
+                    "Found another robot at column 3, the first is on line 2.".to_owned()
+                ),
+                Diagnostic::at(5, "Invalid command character x at column 4.".to_owned()),
+            ],
+            WarehousePlan::validate(&data)
+        );
+    }
 
     #[test]
     fn test_small_example_data_part_1() {
@@ -340,6 +614,7 @@ mod tests {
             "Result for part 2 example should be 9021 but was {}",
             result[1]
         );
+        assert_eq!(result, solve_bitgrid(&data).unwrap());
     }
 
     #[test]
@@ -1,45 +1,156 @@
 use hashbrown::{HashMap, HashSet};
-use std::collections::VecDeque;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+    fmt,
+};
 
-use crate::{increment_2d_index, ortho_dir, Vec2i, Vec2u};
+use crate::{increment_2d_index, ortho_dir, BitGrid, PositionSet, Vec2i, Vec2u};
+
+use super::{
+    input::{
+        check_border, check_grid, check_unique, grid_cells, grid_size, write_grid, Diagnostic,
+        PuzzleInput,
+    },
+    render::{Canvas, Highlight},
+    Variant,
+};
 
 /// Struct to store the state of each path tracker
 struct State(Vec2u, Vec2i, u64, Vec<Vec2u>);
 
+/// Position and facing direction of a reindeer.
+type Pose = (Vec2u, Vec2i);
+
+/// The available implementations for day 16.
+pub const VARIANTS: &[Variant] = &[
+    Variant {
+        name: "naive",
+        solve,
+    },
+    Variant {
+        name: "dijkstra",
+        solve: solve_dijkstra,
+    },
+    Variant {
+        name: "dijkstra-bitgrid",
+        solve: solve_dijkstra_bitgrid,
+    },
+];
+
 /// Cost of an orthogonal turn
 const ORTHOGONAL_COST: u64 = 1000;
 
-/// Solve the problem for day 16, given the provided data.
-pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
-    let (start, end, walls) = parse_maze(input_data)?;
-    let (result_part_1, result_part_2) = solve_parts(start, end, &walls);
-
-    Ok(vec![result_part_1, result_part_2])
+/// The maze of the reindeer olympics, with the walls and the start and end tiles.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Maze {
+    pub size: Vec2u,
+    pub walls: HashSet<Vec2u>,
+    pub start: Vec2u,
+    pub end: Vec2u,
 }
 
-/// Parse start, end, and wall locations from the map.
-fn parse_maze(input_data: &[String]) -> Result<(Vec2u, Vec2u, HashSet<Vec2u>), String> {
-    let mut start = None;
-    let mut end = None;
-    let mut walls = HashSet::new();
+impl PuzzleInput for Maze {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        let size = grid_size(input_data)?;
+        let mut start = None;
+        let mut end = None;
+        let mut walls = HashSet::new();
 
-    for (i, s) in input_data.iter().enumerate() {
-        for (j, c) in s.chars().enumerate() {
+        for (pos, c) in grid_cells(input_data) {
             match c {
                 '#' => {
-                    walls.insert((i, j));
+                    walls.insert(pos);
                 }
-                'S' => start = Some((i, j)),
-                'E' => end = Some((i, j)),
-                _ => {}
+                'S' => start = Some(pos),
+                'E' => end = Some(pos),
+                '.' => {}
+                _ => return Err(format!("Invalid character {} at {:?}.", c, pos)),
             }
         }
+
+        Ok(Maze {
+            size,
+            walls,
+            start: start.ok_or("Failed to find start point")?,
+            end: end.ok_or("Failed to find end point")?,
+        })
     }
 
-    let start = start.ok_or("Failed to find start point")?;
-    let end = end.ok_or("Failed to find start point")?;
+    fn validate(input_data: &[String]) -> Vec<Diagnostic> {
+        let mut diagnostics = check_grid(input_data, 0, |c| "#.SE".contains(c));
+        diagnostics.extend(check_border(input_data, 0, '#'));
+        diagnostics.extend(check_unique(input_data, 0, &['S'], "start"));
+        diagnostics.extend(check_unique(input_data, 0, &['E'], "end"));
+        diagnostics
+    }
+}
 
-    Ok((start, end, walls))
+impl fmt::Display for Maze {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_grid(f, self.size, |pos| {
+            if pos == self.start {
+                'S'
+            } else if pos == self.end {
+                'E'
+            } else if self.walls.contains(&pos) {
+                '#'
+            } else {
+                '.'
+            }
+        })
+    }
+}
+
+/// Solve the problem for day 16, given the provided data.
+pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
+    let Maze {
+        walls, start, end, ..
+    } = Maze::parse_checked(input_data)?;
+    let (result_part_1, result_part_2) = solve_parts(start, end, &walls);
+
+    Ok(vec![result_part_1, result_part_2])
+}
+
+/// Solve the problem for day 16 with Dijkstra's algorithm over position and direction.
+pub fn solve_dijkstra(input_data: &[String]) -> Result<Vec<u64>, String> {
+    let Maze {
+        walls, start, end, ..
+    } = Maze::parse_checked(input_data)?;
+    let (min_cost, tiles) =
+        dijkstra(start, end, &walls).ok_or("Failed to find a path to the end")?;
+
+    Ok(vec![min_cost, tiles.len() as u64])
+}
+
+/// Solve the problem for day 16 with Dijkstra's algorithm, with the walls stored in a bit grid.
+pub fn solve_dijkstra_bitgrid(input_data: &[String]) -> Result<Vec<u64>, String> {
+    let Maze {
+        size,
+        walls,
+        start,
+        end,
+    } = Maze::parse_checked(input_data)?;
+    let walls = BitGrid::from_positions(size, walls);
+    let (min_cost, tiles) =
+        dijkstra(start, end, &walls).ok_or("Failed to find a path to the end")?;
+
+    Ok(vec![min_cost, tiles.len() as u64])
+}
+
+/// Render the maze with the tiles on any of the cheapest paths highlighted.
+pub fn render(input_data: &[String]) -> Result<Canvas, String> {
+    let Maze {
+        walls, start, end, ..
+    } = Maze::parse_checked(input_data)?;
+    let (_, tiles) = dijkstra(start, end, &walls).ok_or("Failed to find a path to the end")?;
+
+    let mut canvas = Canvas::from_lines(input_data);
+    canvas.overlay(tiles, Highlight::Path);
+    canvas.highlight(start, Highlight::Marker);
+    canvas.highlight(end, Highlight::Marker);
+
+    Ok(canvas)
 }
 
 /// Solve part 1
@@ -110,6 +221,74 @@ fn step(state: &State, walls: &HashSet<Vec2u>, visited: &HashMap<Vec2u, u64>) ->
         .collect()
 }
 
+/// Find the minimum cost to reach the end, and the tiles on any of the cheapest paths.
+fn dijkstra<W: PositionSet>(start: Vec2u, end: Vec2u, walls: &W) -> Option<(u64, HashSet<Vec2u>)> {
+    let initial_pose = (start, (0, 1));
+    let mut costs: HashMap<Pose, u64> = HashMap::new();
+    let mut previous: HashMap<Pose, Vec<Pose>> = HashMap::new();
+    let mut heap = BinaryHeap::new();
+    costs.insert(initial_pose, 0);
+    heap.push(Reverse((0, initial_pose)));
+
+    let mut min_cost = None;
+    let mut end_poses = Vec::new();
+    while let Some(Reverse((cost, pose))) = heap.pop() {
+        if min_cost.is_some_and(|min_cost| cost > min_cost) {
+            break;
+        }
+        if costs.get(&pose).is_some_and(|&prev_cost| cost > prev_cost) {
+            continue;
+        }
+        if pose.0 == end {
+            min_cost = Some(cost);
+            end_poses.push(pose);
+            continue;
+        }
+
+        for (next, step_cost) in next_poses(pose, walls) {
+            let next_cost = cost + step_cost;
+            match costs.get(&next) {
+                Some(&prev_cost) if next_cost > prev_cost => (),
+                Some(&prev_cost) if next_cost == prev_cost => {
+                    previous.entry(next).or_default().push(pose)
+                }
+                _ => {
+                    costs.insert(next, next_cost);
+                    previous.insert(next, vec![pose]);
+                    heap.push(Reverse((next_cost, next)));
+                }
+            }
+        }
+    }
+
+    // Backtrack from the end through all equally cheap predecessors.
+    let mut on_path = HashSet::new();
+    while let Some(pose) = end_poses.pop() {
+        if on_path.insert(pose) {
+            end_poses.extend(previous.get(&pose).into_iter().flatten());
+        }
+    }
+    let tiles = on_path.iter().map(|pose| pose.0).collect();
+
+    Some((min_cost?, tiles))
+}
+
+/// Get the poses reachable from the current pose, by a step forward or a turn, with their costs.
+fn next_poses<W: PositionSet>(pose: Pose, walls: &W) -> Vec<(Pose, u64)> {
+    let (pos, dir) = pose;
+    let mut poses = vec![
+        ((pos, ortho_dir(dir, true)), ORTHOGONAL_COST),
+        ((pos, ortho_dir(dir, false)), ORTHOGONAL_COST),
+    ];
+    if let Some(next) = increment_2d_index(pos.0, pos.1, dir.0, dir.1, 1) {
+        if !walls.contains(&next) {
+            poses.push(((next, dir), 1));
+        }
+    }
+
+    poses
+}
+
 /// Check if a tile is valid for stepping into.
 fn is_tile_valid(
     walls: &HashSet<Vec2u>,
@@ -123,6 +302,60 @@ fn is_tile_valid(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::problems::input::testing::{assert_text_round_trip, check_round_trips};
+
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<Maze>("#####\n#..E#\n#.#.#\n#S..#\n#####");
+        check_round_trips(16, |rng| {
+            let size = (rng.range(2, 12), rng.range(2, 12));
+            let start = (rng.range(0, size.0), rng.range(0, size.1));
+            let end = loop {
+                let end = (rng.range(0, size.0), rng.range(0, size.1));
+                if end != start {
+                    break end;
+                }
+            };
+            let walls = (0..size.0)
+                .flat_map(|i| (0..size.1).map(move |j| (i, j)))
+                .filter(|&pos| pos != start && pos != end && rng.chance(0.3))
+                .collect();
+            Maze {
+                size,
+                walls,
+                start,
+                end,
+            }
+        });
+    }
+
+    #[test]
+    fn test_validate() {
+        let data: Vec<String> = "#####\n#S.S#\n#..##\n####"
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            vec![
+                Diagnostic::at(3, "Row has 4 cells, expected 5.".to_owned()),
+                Diagnostic::at(
+                    1,
+                    "Found another start at column 4, the first is on line 2.".to_owned()
+                ),
+                Diagnostic::input("No end found.".to_owned()),
+            ],
+            Maze::validate(&data)
+        );
+
+        let data: Vec<String> = "####\n#S.E\n####".lines().map(String::from).collect();
+        assert_eq!(
+            vec![Diagnostic::at(
+                1,
+                "Expected '#' on the edge at column 4.".to_owned()
+            )],
+            Maze::validate(&data)
+        );
+    }
 
     #[test]
     fn test_example_data() {
@@ -192,6 +425,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_variants_agree() {
+        let map = r"###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############";
+
+        let data: Vec<String> = map.lines().map(String::from).collect();
+
+        for variant in VARIANTS {
+            let result = (variant.solve)(&data).unwrap();
+            assert_eq!(
+                vec![7036, 45],
+                result,
+                "Result for variant {} should be [7036, 45] but was {:?}",
+                variant.name,
+                result
+            );
+        }
+    }
+
     #[test]
     #[cfg(feature = "real_inputs")]
     fn test_day_16() {
@@ -1,25 +1,115 @@
+use std::fmt;
+
 use regex::Regex;
 
+use crate::parse_sequence_from_str;
+
+use super::input::{check_lines, sections, Diagnostic, PuzzleInput};
+
 type Registers = (u64, u64, u64);
 
-/// Solve the problem for day 17, given the provided data.
-pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
-    let (mut registers, program) = parse_input(input_data)?;
+const REGISTER_NAMES: [char; 3] = ['A', 'B', 'C'];
 
-    println!("Program");
-    for val in program.iter() {
-        print!("{}", expand_oct(*val, 1));
+/// The initial registers of the computer and the program it runs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Computer {
+    pub registers: Registers,
+    pub program: Vec<u64>,
+}
+
+impl PuzzleInput for Computer {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        let mut split = input_data.split(|s| s.is_empty());
+        let registers = split.next().ok_or("Failed to parse inputs")?;
+        let program = split
+            .next()
+            .and_then(|lines| lines.first())
+            .ok_or("Failed to parse inputs")?;
+
+        Ok(Computer {
+            registers: parse_registers(registers)?,
+            program: parse_program(program)?,
+        })
     }
 
-    println!("A:\n{}", expand_oct(registers.0, 8));
+    fn validate(input_data: &[String]) -> Vec<Diagnostic> {
+        let sections = sections(input_data);
+        let (_, registers) = sections[0];
+        let mut diagnostics = Vec::new();
+        if registers.len() != REGISTER_NAMES.len() {
+            diagnostics.push(Diagnostic::input(format!(
+                "Expected {} registers, but found {}.",
+                REGISTER_NAMES.len(),
+                registers.len()
+            )));
+        }
+        for (i, (line, name)) in registers.iter().zip(REGISTER_NAMES).enumerate() {
+            let prefix = format!("Register {}: ", name);
+            let valid = line
+                .strip_prefix(&prefix)
+                .is_some_and(|value| value.parse::<u64>().is_ok());
+            if !valid {
+                diagnostics.push(Diagnostic::at(
+                    i,
+                    format!("Expected '{}' followed by a number.", prefix),
+                ));
+            }
+        }
 
-    //println!("Process");
-    let output = compute_program(&mut registers, &program)?;
+        let (start, program) = match sections.get(1) {
+            Some(&(start, lines)) if lines.len() == 1 => (start, lines),
+            Some(&(start, _)) => {
+                diagnostics.push(Diagnostic::at(
+                    start,
+                    "Expected the program on a single line.".to_owned(),
+                ));
+                return diagnostics;
+            }
+            None => {
+                diagnostics.push(Diagnostic::input(
+                    "Missing the program after the registers.".to_owned(),
+                ));
+                return diagnostics;
+            }
+        };
+        diagnostics.extend(check_lines(program, start, |line| {
+            let values = line
+                .strip_prefix("Program: ")
+                .ok_or("Expected the line to start with 'Program: '.")?;
+            let values = parse_sequence_from_str::<u64>(values, ",")?;
+            if let Some(value) = values.iter().find(|&&value| value > 7) {
+                return Err(format!("Value {} is not a 3-bit number.", value));
+            }
+            if values.len() % 2 != 0 {
+                return Err("The last instruction has no operand.".to_owned());
+            }
+            Ok(())
+        }));
 
-    println!("Output");
-    for val in output.iter() {
-        print!("{}", expand_oct(*val, 1));
+        diagnostics
     }
+}
+
+impl fmt::Display for Computer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (reg_a, reg_b, reg_c) = self.registers;
+        writeln!(f, "Register A: {}", reg_a)?;
+        writeln!(f, "Register B: {}", reg_b)?;
+        writeln!(f, "Register C: {}", reg_c)?;
+        writeln!(f)?;
+        let program: Vec<String> = self.program.iter().map(|val| val.to_string()).collect();
+        writeln!(f, "Program: {}", program.join(","))
+    }
+}
+
+/// Solve the problem for day 17, given the provided data.
+pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
+    let Computer {
+        mut registers,
+        program,
+    } = Computer::parse_checked(input_data)?;
+
+    let output = compute_program(&mut registers, &program)?;
 
     Ok(vec![
         output
@@ -33,34 +123,11 @@ pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
     ])
 }
 
-fn expand_oct(val: u64, steps: usize) -> String {
-    let mut s = String::new();
-    for i in (0..steps * 3).step_by(3) {
-        let mask = 0b111;
-        let three_bits = (val >> i) & mask;
-
-        // Process the 3 bits (here we simply print them)
-        let t = format!("{:03b}, ", three_bits);
-        s.push_str(&t);
-    }
-
-    s
-}
-
 fn compute_program(registers: &mut Registers, program: &[u64]) -> Result<Vec<u64>, String> {
     let mut output = Vec::new();
     let mut i = 0;
     let mut increment = true;
     loop {
-        // println!("A: {}", expand_oct(registers.0, 8));
-        // println!("B: {}", expand_oct(registers.1, 8));
-        // println!("C: {}", expand_oct(registers.2, 8));
-        // println!(
-        //     "{}: op: {} operand: {}",
-        //     i,
-        //     expand_oct(program[i], 1),
-        //     expand_oct(program[i + 1], 1)
-        // );
         if let Some(val) = compute_operation(
             program[i],
             program[i + 1],
@@ -114,7 +181,6 @@ fn compute_operation(
         }
         5 => {
             let val = combo_operand(operand, registers)? % 8;
-            println!("Output: {}", expand_oct(val, 1));
             return Ok(Some(val));
         }
         6 => {
@@ -140,15 +206,6 @@ fn combo_operand(operand: u64, registers: &Registers) -> Result<u64, String> {
     }
 }
 
-/// Parse the registers and program commands
-fn parse_input(input_data: &[String]) -> Result<(Registers, Vec<u64>), String> {
-    let mut split = input_data.split(|s| s.is_empty());
-    let registers = split.next().ok_or("Failed to parse inputs")?;
-    let program = &split.next().ok_or("Failed to parse inputs")?[0];
-
-    Ok((parse_registers(registers)?, parse_program(program)?))
-}
-
 /// Read the inital values from the input for the registers
 fn parse_registers(input_data: &[String]) -> Result<Registers, String> {
     let re = Regex::new(r"-?\d+").map_err(|_| "Failed to compile regex.")?;
@@ -198,6 +255,59 @@ fn parse_program(input: &str) -> Result<Vec<u64>, String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::problems::input::testing::{assert_text_round_trip, check_round_trips};
+
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<Computer>(
+            "Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,5,4,3,0",
+        );
+        check_round_trips(17, |rng| Computer {
+            registers: (
+                rng.next_u64(),
+                rng.range(0, 100) as u64,
+                rng.range(0, 100) as u64,
+            ),
+            program: (0..rng.range(0, 16))
+                .map(|_| rng.range(0, 8) as u64)
+                .collect(),
+        });
+    }
+
+    #[test]
+    fn test_validate() {
+        let data: Vec<String> =
+            "Register A: 729\nRegister X: 0\nRegister C: y\n\nProgram: 0,1,5,8,3"
+                .lines()
+                .map(String::from)
+                .collect();
+        assert_eq!(
+            vec![
+                Diagnostic::at(
+                    1,
+                    "Expected 'Register B: ' followed by a number.".to_owned()
+                ),
+                Diagnostic::at(
+                    2,
+                    "Expected 'Register C: ' followed by a number.".to_owned()
+                ),
+                Diagnostic::at(4, "Value 8 is not a 3-bit number.".to_owned()),
+            ],
+            Computer::validate(&data)
+        );
+
+        let data: Vec<String> = "Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,5"
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            vec![Diagnostic::at(
+                4,
+                "The last instruction has no operand.".to_owned()
+            )],
+            Computer::validate(&data)
+        );
+    }
 
     #[test]
     fn test_example_data() {
@@ -1,16 +1,72 @@
-use hashbrown::HashSet;
-use std::collections::VecDeque;
+use hashbrown::{HashMap, HashSet};
+use std::{collections::VecDeque, fmt};
 
-use crate::{increment_2d_index, parse_pair_from_str, Vec2u};
+use crate::{parse_pair_from_str, BitGrid, PositionSet, UnionFind, Vec2u};
 
-const DIRECTIONS: &[(isize, isize)] = &[(-1, 0), (1, 0), (0, -1), (0, 1)];
+use super::{
+    geometry::neighbours_4,
+    input::{check_lines, Diagnostic, PuzzleInput},
+    Variant,
+};
 
 const MAP_SIZE: Vec2u = (71, 71);
 
+/// The available implementations for day 18.
+pub const VARIANTS: &[Variant] = &[
+    Variant {
+        name: "hashed",
+        solve,
+    },
+    Variant {
+        name: "bitgrid",
+        solve: solve_bitgrid,
+    },
+];
+
+/// The positions of the falling bytes, in the order they fall.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FallingBytes {
+    pub bytes: Vec<Vec2u>,
+}
+
+impl PuzzleInput for FallingBytes {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        let bytes = input_data
+            .iter()
+            .map(|s| parse_pair_from_str(s, ","))
+            .collect::<Result<_, _>>()?;
+
+        Ok(FallingBytes { bytes })
+    }
+
+    fn validate(input_data: &[String]) -> Vec<Diagnostic> {
+        check_lines(input_data, 0, |line| {
+            let byte: Vec2u = parse_pair_from_str(line, ",")?;
+            if byte.0 >= MAP_SIZE.0 || byte.1 >= MAP_SIZE.1 {
+                return Err(format!(
+                    "Byte {:?} is outside the {}x{} memory space.",
+                    byte, MAP_SIZE.0, MAP_SIZE.1
+                ));
+            }
+            Ok(())
+        })
+    }
+}
+
+impl fmt::Display for FallingBytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (x, y) in &self.bytes {
+            writeln!(f, "{},{}", x, y)?;
+        }
+        Ok(())
+    }
+}
+
 /// Solve the problem for day 18, given the provided data.
 pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
-    let result_part_1 = solve_for_size(input_data, 0, 1024, MAP_SIZE)?;
-    let result_part_2 = find_unsolveable_config(input_data, MAP_SIZE)?;
+    let bytes = FallingBytes::parse_checked(input_data)?.bytes;
+    let result_part_1 = solve_for_size(&bytes, 0, 1024, MAP_SIZE)?;
+    let result_part_2 = find_unsolveable_config(&bytes, 1024, MAP_SIZE)?;
     Ok(vec![
         result_part_1,
         result_part_2.0 as u64,
@@ -18,57 +74,111 @@ pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
     ])
 }
 
-/// Solve the problem for day 18, given the provided data.
+/// Solve the problem for day 18, with the corrupted bytes of part 1 stored in a bit grid.
+pub fn solve_bitgrid(input_data: &[String]) -> Result<Vec<u64>, String> {
+    let bytes = FallingBytes::parse_checked(input_data)?.bytes;
+    let fallen = BitGrid::from_positions(MAP_SIZE, fallen_bytes(&bytes, 0, 1024)?);
+    let result_part_1 = trace((0, 0), MAP_SIZE, &fallen)? as u64;
+    let result_part_2 = find_unsolveable_config(&bytes, 1024, MAP_SIZE)?;
+    Ok(vec![
+        result_part_1,
+        result_part_2.0 as u64,
+        result_part_2.1 as u64,
+    ])
+}
+
+/// Solve part 1 with the bytes from `start` to `end` fallen on a map of the size.
 pub fn solve_for_size(
-    input_data: &[String],
+    bytes: &[Vec2u],
     start: usize,
     end: usize,
     map_size: Vec2u,
 ) -> Result<u64, String> {
-    let bytes = parse_bytes(input_data, start, end)?;
+    let fallen = fallen_bytes(bytes, start, end)?;
 
-    let result = trace((0, 0), map_size, &bytes)?;
+    let result = trace((0, 0), map_size, &fallen)?;
 
     Ok(result as u64)
 }
 
-/// Find unsolveable configuration of bytes, starting from part 1 and adding one at a time.
-fn find_unsolveable_config(input_data: &[String], map_size: Vec2u) -> Result<Vec2u, String> {
-    let mut bytes = parse_bytes(input_data, 0, 1024)?;
+/// Find the first byte that blocks the path, when adding the bytes from `start` one at a time.
+/// All bytes are added at once, and then removed in reverse order while merging the freed cells
+/// with their free neighbours, until the start and the end are connected again.
+fn find_unsolveable_config(
+    bytes: &[Vec2u],
+    start: usize,
+    map_size: Vec2u,
+) -> Result<Vec2u, String> {
+    // Index of the first byte falling on each cell
+    let mut corrupted_at = HashMap::new();
+    for (i, &byte) in bytes.iter().enumerate() {
+        if byte.0 >= map_size.0 || byte.1 >= map_size.1 {
+            return Err(format!(
+                "Byte {} at {},{} is outside the {}x{} map.",
+                i, byte.0, byte.1, map_size.0, map_size.1
+            ));
+        }
+        corrupted_at.entry(byte).or_insert(i);
+    }
 
-    for i in 1024..input_data.len() {
-        let next_byte = add_byte(input_data, &mut bytes, i)?;
-        let result = trace((0, 0), map_size, &bytes);
+    let index = |pos: Vec2u| pos.0 * map_size.1 + pos.1;
+    let end = index((map_size.0 - 1, map_size.1 - 1));
+    let mut cells = UnionFind::new(map_size.0 * map_size.1);
+    for i in 0..map_size.0 {
+        for j in 0..map_size.1 {
+            if !corrupted_at.contains_key(&(i, j)) {
+                free_cell((i, j), map_size, bytes.len(), &corrupted_at, &mut cells);
+            }
+        }
+    }
+    if cells.connected(0, end) {
+        return Err("Failed to find unsolveable solution.".to_owned());
+    }
 
-        if result.is_err() {
-            return Ok(next_byte);
+    for i in (start + 1..bytes.len()).rev() {
+        if corrupted_at[&bytes[i]] == i {
+            free_cell(bytes[i], map_size, i, &corrupted_at, &mut cells);
+            if cells.connected(0, end) {
+                return Ok(bytes[i]);
+            }
         }
     }
 
-    Err("Failed to find unsolveable solution.".to_owned())
+    bytes
+        .get(start)
+        .copied()
+        .ok_or("Failed to find unsolveable solution.".to_owned())
 }
 
-/// Parse the bytes from the input, based on start and end index
-fn parse_bytes(input_data: &[String], start: usize, end: usize) -> Result<HashSet<Vec2u>, String> {
-    input_data[start..end]
-        .iter()
-        .map(|s| parse_pair_from_str(s, ","))
-        .collect()
+/// Merge a free cell with its free neighbours, where only the first `num_bytes` bytes have fallen.
+fn free_cell(
+    pos: Vec2u,
+    map_size: Vec2u,
+    num_bytes: usize,
+    corrupted_at: &HashMap<Vec2u, usize>,
+    cells: &mut UnionFind,
+) {
+    for next in neighbours_4(pos, map_size)
+        .filter(|next| corrupted_at.get(next).is_none_or(|&i| i >= num_bytes))
+    {
+        cells.union(pos.0 * map_size.1 + pos.1, next.0 * map_size.1 + next.1);
+    }
 }
 
-/// Add an additional byte from the array. Returns the location of the added byte.
-fn add_byte(
-    input_data: &[String],
-    bytes: &mut HashSet<Vec2u>,
-    index: usize,
-) -> Result<Vec2u, String> {
-    let byte = parse_pair_from_str(&input_data[index], ",")?;
-    bytes.insert(byte);
-    Ok(byte)
+/// Collect the bytes from the start to the end index
+fn fallen_bytes(bytes: &[Vec2u], start: usize, end: usize) -> Result<HashSet<Vec2u>, String> {
+    bytes
+        .get(start..end)
+        .map(|fallen| fallen.iter().copied().collect())
+        .ok_or(format!(
+            "Expected at least {} bytes, but found {}.",
+            end,
+            bytes.len()
+        ))
 }
 
 /// Trace path using bfs search
-fn trace(start: Vec2u, map_size: Vec2u, bytes: &HashSet<Vec2u>) -> Result<usize, String> {
+fn trace<B: PositionSet>(start: Vec2u, map_size: Vec2u, bytes: &B) -> Result<usize, String> {
     let mut queue = VecDeque::new();
     queue.push_back((start, 0));
     let end = (map_size.0 - 1, map_size.1 - 1);
@@ -86,22 +196,14 @@ fn trace(start: Vec2u, map_size: Vec2u, bytes: &HashSet<Vec2u>) -> Result<usize,
 }
 
 /// Compute valid steps from the current pos
-fn step(
+fn step<B: PositionSet>(
     pos: &(Vec2u, usize),
-    bytes: &HashSet<Vec2u>,
+    bytes: &B,
     visited: &HashSet<Vec2u>,
     map_size: &Vec2u,
 ) -> Vec<(Vec2u, usize)> {
-    DIRECTIONS
-        .iter()
-        .filter_map(|&next_dir| {
-            increment_2d_index(pos.0 .0, pos.0 .1, next_dir.0, next_dir.1, 1).filter(|next| {
-                !bytes.contains(next)
-                    && !visited.contains(next)
-                    && next.0 < map_size.0
-                    && next.1 < map_size.1
-            })
-        })
+    neighbours_4(pos.0, *map_size)
+        .filter(|next| !bytes.contains(next) && !visited.contains(next))
         .map(|p| (p, pos.1 + 1))
         .collect()
 }
@@ -109,6 +211,32 @@ fn step(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::problems::input::testing::{assert_text_round_trip, check_round_trips};
+
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<FallingBytes>("5,4\n4,2\n4,5\n3,0");
+        check_round_trips(18, |rng| FallingBytes {
+            bytes: (0..rng.range(0, 20))
+                .map(|_| (rng.range(0, 71), rng.range(0, 71)))
+                .collect(),
+        });
+    }
+
+    #[test]
+    fn test_validate() {
+        let data: Vec<String> = "5,4\n4,x\n71,0".lines().map(String::from).collect();
+        assert_eq!(
+            vec![
+                Diagnostic::at(1, "Failed to parse usize from x".to_owned()),
+                Diagnostic::at(
+                    2,
+                    "Byte (71, 0) is outside the 71x71 memory space.".to_owned()
+                ),
+            ],
+            FallingBytes::validate(&data)
+        );
+    }
 
     #[test]
     fn test_example_data() {
@@ -139,6 +267,7 @@ mod tests {
 2,0";
 
         let data: Vec<String> = map.lines().map(String::from).collect();
+        let data = FallingBytes::parse(&data).unwrap().bytes;
 
         let result = solve_for_size(&data, 0, 12, (7, 7)).unwrap();
         assert_eq!(
@@ -146,6 +275,23 @@ mod tests {
             "Result for part 1 example should be 22 but was {}",
             result
         );
+        let bytes = BitGrid::from_positions((7, 7), fallen_bytes(&data, 0, 12).unwrap());
+        assert_eq!(Ok(22), trace((0, 0), (7, 7), &bytes));
+
+        let result = find_unsolveable_config(&data, 12, (7, 7)).unwrap();
+        assert_eq!(
+            (6, 1),
+            result,
+            "Result for part 2 example should be (6, 1) but was {:?}",
+            result
+        );
+
+        let mut outside = data.clone();
+        outside.push((7, 0));
+        assert_eq!(
+            Err("Byte 25 at 7,0 is outside the 7x7 map.".to_owned()),
+            find_unsolveable_config(&outside, 12, (7, 7))
+        );
     }
 
     #[test]
@@ -1,8 +1,119 @@
-use std::collections::VecDeque;
+use hashbrown::HashSet;
+use std::{collections::VecDeque, fmt};
+
+use crate::Trie;
+
+use super::{
+    input::{check_lines, Diagnostic, PuzzleInput},
+    Variant,
+};
+
+/// The colours of the stripes on the towels.
+const COLOURS: &str = "wubrg";
+
+/// The available implementations for day 19.
+pub const VARIANTS: &[Variant] = &[
+    Variant {
+        name: "naive",
+        solve,
+    },
+    Variant {
+        name: "fast",
+        solve: solve_fast,
+    },
+    Variant {
+        name: "trie",
+        solve: solve_trie,
+    },
+];
+
+/// The available towel patterns and the designs to make from them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Towels {
+    pub patterns: Vec<String>,
+    pub designs: Vec<String>,
+}
+
+impl Towels {
+    /// Borrow the designs and the patterns as string slices.
+    fn as_strs(&self) -> (Vec<&str>, Vec<&str>) {
+        (
+            self.designs.iter().map(|s| s.as_str()).collect(),
+            self.patterns.iter().map(|s| s.as_str()).collect(),
+        )
+    }
+}
+
+impl PuzzleInput for Towels {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        let (first, rest) = input_data
+            .split_first()
+            .ok_or("Failed to find the towel patterns.")?;
+        if rest.first().is_some_and(|line| !line.is_empty()) {
+            return Err("Expected an empty line after the towel patterns.".to_owned());
+        }
+
+        Ok(Towels {
+            patterns: first.split(',').map(|s| s.trim().to_owned()).collect(),
+            designs: rest.iter().skip(1).cloned().collect(),
+        })
+    }
+
+    fn validate(input_data: &[String]) -> Vec<Diagnostic> {
+        let Some(patterns) = input_data.first() else {
+            return vec![Diagnostic::input("Missing the towel patterns.".to_owned())];
+        };
+
+        let check_stripes = |stripes: &str| match stripes
+            .chars()
+            .enumerate()
+            .find(|(_, c)| !COLOURS.contains(*c))
+        {
+            Some((j, c)) => Err(format!("Invalid colour '{}' at position {}.", c, j + 1)),
+            None if stripes.is_empty() => Err("Found an empty towel.".to_owned()),
+            None => Ok(()),
+        };
+
+        let mut diagnostics: Vec<Diagnostic> = patterns
+            .split(',')
+            .filter_map(|pattern| {
+                check_stripes(pattern.trim())
+                    .err()
+                    .map(|err| Diagnostic::at(0, format!("Pattern '{}': {}", pattern.trim(), err)))
+            })
+            .collect();
+        match input_data.get(1) {
+            Some(line) if line.is_empty() => {
+                diagnostics.extend(check_lines(&input_data[2..], 2, check_stripes));
+            }
+            Some(_) => diagnostics.push(Diagnostic::at(
+                1,
+                "Expected an empty line after the towel patterns.".to_owned(),
+            )),
+            None => diagnostics.push(Diagnostic::input(
+                "Missing the designs after the towel patterns.".to_owned(),
+            )),
+        }
+
+        diagnostics
+    }
+}
+
+impl fmt::Display for Towels {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.patterns.join(", "))?;
+        writeln!(f)?;
+        for design in &self.designs {
+            writeln!(f, "{}", design)?;
+        }
+        Ok(())
+    }
+}
 
 /// Solve the problem for day 19, given the provided data.
 pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
-    let (designs, patterns) = parse_input(input_data);
+    let towels = Towels::parse_checked(input_data)?;
+    let (designs, patterns) = towels.as_strs();
     let solutions: Vec<usize> = designs
         .iter()
         .map(|&design| compute_num_solutions(design, &patterns))
@@ -14,12 +125,74 @@ pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
     ])
 }
 
-/// Parse the input data into patterns and designs.
-fn parse_input(input_data: &[String]) -> (Vec<&str>, Vec<&str>) {
-    let patterns = input_data[0].split(',').map(|s| s.trim()).collect();
-    let designs = input_data[2..].iter().map(|s| s.as_str()).collect();
+/// Solve the problem for day 19 by looking up each substring of a design in a set of patterns.
+pub fn solve_fast(input_data: &[String]) -> Result<Vec<u64>, String> {
+    let towels = Towels::parse_checked(input_data)?;
+    let (designs, patterns) = towels.as_strs();
+    let pattern_set: HashSet<&str> = patterns.iter().copied().collect();
+    let max_len = patterns.iter().map(|p| p.len()).max().unwrap_or(0);
+    let solutions: Vec<usize> = designs
+        .iter()
+        .map(|&design| count_arrangements(design, &pattern_set, max_len))
+        .collect();
+
+    Ok(vec![
+        solutions.iter().filter(|&s| *s > 0).count() as u64,
+        solutions.iter().sum::<usize>() as u64,
+    ])
+}
+
+/// Solve the problem for day 19 by walking a trie of the patterns from each position of a design.
+pub fn solve_trie(input_data: &[String]) -> Result<Vec<u64>, String> {
+    let towels = Towels::parse_checked(input_data)?;
+    let (designs, patterns) = towels.as_strs();
+    let mut trie = Trie::new();
+    for pattern in patterns {
+        trie.insert(pattern.bytes());
+    }
+    let solutions: Vec<usize> = designs
+        .iter()
+        .map(|&design| count_arrangements_trie(design, &trie))
+        .collect();
+
+    Ok(vec![
+        solutions.iter().filter(|&s| *s > 0).count() as u64,
+        solutions.iter().sum::<usize>() as u64,
+    ])
+}
+
+/// Count the arrangements of the design, with the patterns matching at each position found in the trie.
+fn count_arrangements_trie(design: &str, trie: &Trie<u8>) -> usize {
+    let mut ways = vec![0; design.len() + 1];
+    ways[0] = 1;
+    for start in 0..design.len() {
+        if ways[start] == 0 {
+            continue;
+        }
+        for len in trie.prefix_lengths(design[start..].bytes()) {
+            ways[start + len] += ways[start];
+        }
+    }
+
+    ways[design.len()]
+}
+
+/// Count the arrangements of the design with a forward pass over all prefix lengths.
+fn count_arrangements(design: &str, patterns: &HashSet<&str>, max_len: usize) -> usize {
+    let mut ways = vec![0; design.len() + 1];
+    ways[0] = 1;
+    for start in 0..design.len() {
+        if ways[start] == 0 {
+            continue;
+        }
+        for end in start + 1..=design.len().min(start + max_len) {
+            if patterns.contains(&design[start..end]) {
+                ways[end] += ways[start];
+            }
+        }
+    }
 
-    (designs, patterns)
+    ways[design.len()]
 }
 
 /// Check if the design can be made from the available patterns.
@@ -76,6 +249,47 @@ fn find_matches_at(design: &str, start: usize, patterns: &[&str]) -> Vec<usize>
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::problems::input::testing::{assert_text_round_trip, check_round_trips, Rng};
+
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<Towels>("r, wr, b, g, bwu, rb, gb, br\n\nbrwrr\nbggr\ngbbr");
+        check_round_trips(19, |rng| {
+            let towel = |rng: &mut Rng, max_len| {
+                (0..rng.range(1, max_len))
+                    .map(|_| rng.choose(&['w', 'u', 'b', 'r', 'g']))
+                    .collect::<String>()
+            };
+            Towels {
+                patterns: (0..rng.range(1, 8)).map(|_| towel(rng, 4)).collect(),
+                designs: (0..rng.range(0, 8)).map(|_| towel(rng, 12)).collect(),
+            }
+        });
+    }
+
+    #[test]
+    fn test_validate() {
+        let data: Vec<String> = "r, wr, x\n\nbrwrr\nbgq".lines().map(String::from).collect();
+        assert_eq!(
+            vec![
+                Diagnostic::at(
+                    0,
+                    "Pattern 'x': Invalid colour 'x' at position 1.".to_owned()
+                ),
+                Diagnostic::at(3, "Invalid colour 'q' at position 3.".to_owned()),
+            ],
+            Towels::validate(&data)
+        );
+
+        let data: Vec<String> = "r, b\nbr".lines().map(String::from).collect();
+        assert_eq!(
+            vec![Diagnostic::at(
+                1,
+                "Expected an empty line after the towel patterns.".to_owned()
+            )],
+            Towels::validate(&data)
+        );
+    }
 
     #[test]
     fn test_example_data() {
@@ -105,6 +319,33 @@ bbrgwb";
         );
     }
 
+    #[test]
+    fn test_variants_agree() {
+        let map = r"r, wr, b, g, bwu, rb, gb, br
+
+brwrr
+bggr
+gbbr
+rrbgbr
+ubwu
+bwurrg
+brgr
+bbrgwb";
+
+        let data: Vec<String> = map.lines().map(String::from).collect();
+
+        for variant in VARIANTS {
+            let result = (variant.solve)(&data).unwrap();
+            assert_eq!(
+                vec![6, 16],
+                result,
+                "Result for variant {} should be [6, 16] but was {:?}",
+                variant.name,
+                result
+            );
+        }
+    }
+
     #[test]
     #[cfg(feature = "real_inputs")]
     fn test_day_19() {
@@ -1,129 +1,467 @@
+use std::{fmt, ops::Range};
+
 use crate::parse_sequence_from_str;
 
-// Alias for a difference between two values, given as the index and the size.
-type DiffTuple = (usize, i32);
+use super::{
+    input::{check_lines, Diagnostic, PuzzleInput},
+    report::{Report, Section},
+};
+
+/// The reports of the reactor, each a sequence of levels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reports {
+    pub reports: Vec<Vec<i32>>,
+}
+
+impl PuzzleInput for Reports {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        let reports = input_data
+            .iter()
+            .map(|line| {
+                parse_sequence_from_str(line, " ")
+                    .map_err(|_| format!("Failed to parse {} into integers", line))
+            })
+            .collect::<Result<Vec<Vec<i32>>, String>>()?;
+
+        Ok(Reports { reports })
+    }
+
+    fn validate(input_data: &[String]) -> Vec<Diagnostic> {
+        check_lines(input_data, 0, |line| {
+            parse_sequence_from_str::<i32>(line, " ")
+        })
+    }
+}
+
+impl fmt::Display for Reports {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for report in &self.reports {
+            let levels: Vec<String> = report.iter().map(|level| level.to_string()).collect();
+            writeln!(f, "{}", levels.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// The allowed size of a step between two levels in a safe report.
+pub const SAFE_STEPS: Range<i32> = 1..4;
 
 /// Solve the problem for day two, given the provided data.
 pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
     // Parse inputs
-    let input_sequences = input_data
-        .iter()
-        .map(|line| {
-            parse_sequence_from_str(line, " ")
-                .map_err(|_| format!("Failed to parse {} into integers", line))
-        })
-        .collect::<Result<Vec<Vec<i32>>, String>>()?;
+    let input_sequences = Reports::parse_checked(input_data)?.reports;
 
     // Part 1
-    let num_safe_sequences: u64 = input_sequences
-        .iter()
-        .filter(|sequence| validate_sequence(sequence))
-        .count()
-        .try_into()
-        .map_err(|_| "Value is too large to fit in u32")?;
+    let num_safe_sequences = count_safe(&input_sequences, &Dampener::new(0, SAFE_STEPS));
 
     // Part 2
-    let num_safe_sequences_with_dampening: u64 = input_sequences
-        .iter()
-        .filter(|sequence| validate_sequence_with_dampening(sequence))
-        .count()
-        .try_into()
-        .map_err(|_| "Value is too large to fit in u32")?;
+    let num_safe_sequences_with_dampening =
+        count_safe(&input_sequences, &Dampener::new(1, SAFE_STEPS));
 
-    Ok(vec![num_safe_sequences, num_safe_sequences_with_dampening])
+    Ok(vec![
+        num_safe_sequences as u64,
+        num_safe_sequences_with_dampening as u64,
+    ])
 }
 
-// Validate a sequence of numbers. Valid if all increasing or decreasing and max step <= 3.
-fn validate_sequence(sequence: &[i32]) -> bool {
-    let mut prev_diff: i32 = 0;
-    for i in 1..sequence.len() {
-        let diff = sequence[i - 1] - (sequence[i]);
-        if is_diff_unsafe(i, diff, prev_diff) {
-            return false;
-        }
-        prev_diff = diff;
+/// Classify each report as safe, safe after removing levels, or unsafe, with the first unsafe
+/// step and the levels the dampener removes.
+pub fn explain(input_data: &[String]) -> Result<Report, String> {
+    let reports = Reports::parse_checked(input_data)?.reports;
+    let dampener = Dampener::new(1, SAFE_STEPS);
+
+    let mut classified = Section::new(
+        "Reports",
+        &[
+            "Line",
+            "Levels",
+            "Safety",
+            "Reason",
+            "Position",
+            "Step",
+            "Removed level",
+        ],
+    );
+    for (i, sequence) in reports.iter().enumerate() {
+        let levels: Vec<String> = sequence.iter().map(|level| level.to_string()).collect();
+        let safety = dampener.classify(sequence);
+        let (step, removed) = match &safety {
+            ReportSafety::Safe => (None, None),
+            ReportSafety::Dampened { step, removed } => (Some(step), Some(removed)),
+            ReportSafety::Unsafe(step) => (Some(step), None),
+        };
+        let removed = removed.map(|removed| {
+            let removed: Vec<String> = removed
+                .iter()
+                .map(|&index| format!("{} (position {})", sequence[index], index + 1))
+                .collect();
+            removed.join(", ")
+        });
+        classified.push_row(vec![
+            (i + 1).into(),
+            levels.join(" ").into(),
+            safety.label().into(),
+            step.map(|step| step.reason.to_string()).into(),
+            step.map(|step| step.position + 1).into(),
+            step.map(|step| {
+                format!(
+                    "{} -> {}",
+                    sequence[step.position - 1],
+                    sequence[step.position]
+                )
+            })
+            .into(),
+            removed.into(),
+        ]);
     }
 
-    true
+    Ok(Report::new(vec![classified]))
 }
 
-// Check if the diff can be considered unsafe
-fn is_diff_unsafe(i: usize, diff: i32, prev_diff: i32) -> bool {
-    !(1..4).contains(&diff.abs()) || (i > 1 && prev_diff.signum() != diff.signum())
+/// Why a step between two levels makes a report unsafe.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnsafeReason {
+    DirectionChange,
+    ZeroStep,
+    StepTooSmall,
+    StepTooLarge,
 }
 
-// Validate a sequence of numbers. Valid if all increasing or decreasing and max step <= 3, allowing one bad number in a sequence.
-fn validate_sequence_with_dampening(sequence: &[i32]) -> bool {
-    let diffs: Vec<i32> = (1..sequence.len())
-        .map(|i| sequence[i] - sequence[i - 1])
-        .collect();
-
-    // Validate diffs
-    let problematic = get_problematic_diffs(&diffs);
-
-    if problematic.is_empty() {
-        return true;
-    } else if problematic.len() < 3 {
-        // If the sequence has diffs causing problems but is potentally solveable, try to remove the entry before or after.
-        let mut valid_found = false;
-        for problematic_diff in problematic {
-            if validate_sequence_with_removed(sequence, problematic_diff.0)
-                || validate_sequence_with_removed(sequence, problematic_diff.0 + 1)
-            {
-                valid_found = true;
-                break;
-            }
-        }
-        return valid_found;
+impl fmt::Display for UnsafeReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self {
+            UnsafeReason::DirectionChange => "direction change",
+            UnsafeReason::ZeroStep => "zero step",
+            UnsafeReason::StepTooSmall => "step too small",
+            UnsafeReason::StepTooLarge => "step too large",
+        };
+        write!(f, "{}", reason)
     }
-
-    false
 }
 
-// Find the diffs that are causing issues
-fn get_problematic_diffs(diffs: &[i32]) -> Vec<DiffTuple> {
-    let (increases, decreases, invalid) = sort_increment_types(diffs);
+/// The first unsafe step of a report, given by the index of the level it ends at.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UnsafeStep {
+    pub position: usize,
+    pub reason: UnsafeReason,
+}
 
-    let mut problematic = if increases.len() > decreases.len() {
-        decreases.to_vec()
-    } else {
-        increases.to_vec()
-    };
+/// The safety of a single report.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReportSafety {
+    Safe,
+    /// Safe after removing the levels at the indices.
+    Dampened {
+        step: UnsafeStep,
+        removed: Vec<usize>,
+    },
+    Unsafe(UnsafeStep),
+}
 
-    problematic.extend_from_slice(&invalid);
-    problematic
+impl ReportSafety {
+    /// A short name of the class of the report.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReportSafety::Safe => "safe",
+            ReportSafety::Dampened { .. } => "safe after removal",
+            ReportSafety::Unsafe(_) => "unsafe",
+        }
+    }
 }
 
-// Validate the sequence after removing the item at an index
-fn validate_sequence_with_removed(sequence: &[i32], index: usize) -> bool {
-    // This could probably be done with some rule, and not actually removing the element...
-    let mut temp_s = sequence.to_vec();
-    temp_s.remove(index);
+/// Count the reports that are safe with the dampener.
+pub fn count_safe(reports: &[Vec<i32>], dampener: &Dampener) -> usize {
+    reports
+        .iter()
+        .filter(|report| dampener.is_safe(report))
+        .count()
+}
 
-    validate_sequence(&temp_s)
+/// The problem dampener, tolerating up to a number of bad levels in a report. A report is safe
+/// if its levels are all increasing or all decreasing, with every step in the allowed range.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dampener {
+    pub max_removals: usize,
+    pub steps: Range<i32>,
 }
 
-// Count the number of occurrances of each diff type, classified as increasing, decreasing or invalid (flat or too big jump)
-fn sort_increment_types(diffs: &[i32]) -> (Vec<DiffTuple>, Vec<DiffTuple>, Vec<DiffTuple>) {
-    let (increases, decreases, invalid) = diffs.iter().enumerate().fold(
-        (Vec::new(), Vec::new(), Vec::new()),
-        |(mut inc, mut dec, mut inv), (i, &value)| {
-            if (1..4).contains(&value) {
-                inc.push((i, value));
-            } else if (-3..0).contains(&value) {
-                dec.push((i, value));
+impl Dampener {
+    pub fn new(max_removals: usize, steps: Range<i32>) -> Dampener {
+        Dampener {
+            max_removals,
+            steps,
+        }
+    }
+
+    /// Check if the report is safe after removing at most the max number of levels.
+    pub fn is_safe(&self, report: &[i32]) -> bool {
+        self.removed_levels(report).is_some()
+    }
+
+    /// Find the fewest levels to remove to make the report safe, as their indices.
+    /// Returns [`None`] if that takes more than the max number of removals.
+    pub fn removed_levels(&self, report: &[i32]) -> Option<Vec<usize>> {
+        [1, -1]
+            .into_iter()
+            .filter_map(|direction| self.removed_levels_in_direction(report, direction))
+            .min_by_key(|removed| removed.len())
+    }
+
+    /// Find the longest chain of levels with safe steps in the direction, which is the report
+    /// with the fewest levels removed. Removing more than the max number of levels between two
+    /// kept levels is never needed, so only that many previous levels are checked for each level.
+    fn removed_levels_in_direction(&self, report: &[i32], direction: i64) -> Option<Vec<usize>> {
+        let len = report.len();
+        let window = self.max_removals + 1;
+
+        // The length of the longest chain ending at each level, and the level before it
+        let mut chains: Vec<Option<(usize, Option<usize>)>> = vec![None; len];
+        for i in 0..len {
+            if i < window {
+                chains[i] = Some((1, None));
+            }
+            for j in i.saturating_sub(window)..i {
+                let Some((chain_len, _)) = chains[j] else {
+                    continue;
+                };
+                // Prefer the latest previous level on ties, removing the earlier levels
+                if self.is_step_safe(report[j], report[i], direction)
+                    && chains[i].is_none_or(|(best, _)| chain_len + 1 >= best)
+                {
+                    chains[i] = Some((chain_len + 1, Some(j)));
+                }
+            }
+        }
+
+        // Prefer the latest last level on ties, as for the previous levels
+        let start = len.saturating_sub(window);
+        let best_end = chains[start..]
+            .iter()
+            .enumerate()
+            .filter_map(|(offset, chain)| chain.map(|(chain_len, _)| (chain_len, start + offset)))
+            .max();
+
+        let mut kept = vec![false; len];
+        let mut next = best_end.map(|(_, end)| end);
+        while let Some(i) = next {
+            kept[i] = true;
+            next = chains[i].and_then(|(_, prev)| prev);
+        }
+        let removed: Vec<usize> = (0..len).filter(|&i| !kept[i]).collect();
+
+        (removed.len() <= self.max_removals).then_some(removed)
+    }
+
+    /// Classify the report, with the first unsafe step of an unsafe report.
+    pub fn classify(&self, report: &[i32]) -> ReportSafety {
+        let Some(step) = self.find_unsafe_step(report) else {
+            return ReportSafety::Safe;
+        };
+        match self.removed_levels(report) {
+            Some(removed) => ReportSafety::Dampened { step, removed },
+            None => ReportSafety::Unsafe(step),
+        }
+    }
+
+    /// Find the first step that is unsafe, in the direction of the first step that changes the
+    /// level. A step is unsafe when it is outside the allowed range in that direction.
+    pub fn find_unsafe_step(&self, report: &[i32]) -> Option<UnsafeStep> {
+        let mut direction = 0;
+        for i in 1..report.len() {
+            let diff = report[i] as i64 - report[i - 1] as i64;
+            if direction == 0 {
+                direction = diff.signum();
+            }
+            let step = diff * direction;
+            let reason = if step < self.steps.start as i64 {
+                Some(match step.signum() {
+                    -1 => UnsafeReason::DirectionChange,
+                    0 => UnsafeReason::ZeroStep,
+                    _ => UnsafeReason::StepTooSmall,
+                })
+            } else if step >= self.steps.end as i64 {
+                Some(UnsafeReason::StepTooLarge)
             } else {
-                inv.push((i, value));
+                None
+            };
+            if let Some(reason) = reason {
+                return Some(UnsafeStep {
+                    position: i,
+                    reason,
+                });
             }
-            (inc, dec, inv)
-        },
-    );
+        }
+
+        None
+    }
 
-    (increases, decreases, invalid)
+    fn is_step_safe(&self, from: i32, to: i32, direction: i64) -> bool {
+        let step = (to as i64 - from as i64) * direction;
+        (self.steps.start as i64..self.steps.end as i64).contains(&step)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::problems::{
+        input::testing::{assert_text_round_trip, check_round_trips, Rng},
+        report::Value,
+    };
+
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<Reports>("7 6 4 2 1\n1 2 7 8 9\n9 7 6 2 1");
+        check_round_trips(2, |rng| Reports {
+            reports: (0..rng.range(0, 10))
+                .map(|_| {
+                    (0..rng.range(1, 9))
+                        .map(|_| rng.range(0, 100) as i32)
+                        .collect()
+                })
+                .collect(),
+        });
+    }
+
+    #[test]
+    fn test_validate() {
+        let data: Vec<String> = "7 6 4 2 1\n1 2 x 4\n9 7 6"
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            vec![Diagnostic::at(1, "Failed to parse i32 from x".to_owned()),],
+            Reports::validate(&data)
+        );
+    }
+
+    #[test]
+    fn test_explain() {
+        let data: Vec<String> = "7 6 4 2 1\n1 2 7 8 9\n9 7 6 2 1\n1 3 2 4 5\n8 6 4 4 1\n1 3 6 7 9"
+            .lines()
+            .map(String::from)
+            .collect();
+        let report = explain(&data).unwrap();
+        let rows = &report.sections[0].rows;
+
+        assert_eq!(6, rows.len());
+        assert_eq!(
+            vec![
+                Value::from(4),
+                "1 3 2 4 5".into(),
+                "safe after removal".into(),
+                "direction change".into(),
+                3.into(),
+                "3 -> 2".into(),
+                "3 (position 2)".into()
+            ],
+            rows[3]
+        );
+        assert_eq!(Value::from("safe"), rows[0][2]);
+        assert_eq!(Value::Empty, rows[0][3]);
+        assert_eq!(Value::from("step too large"), rows[1][3]);
+        assert_eq!(Value::Empty, rows[1][6]);
+    }
+
+    #[test]
+    fn test_classify() {
+        let dampener = Dampener::new(1, SAFE_STEPS);
+        assert_eq!(ReportSafety::Safe, dampener.classify(&[7, 6, 4, 2, 1]));
+        assert_eq!(
+            ReportSafety::Unsafe(UnsafeStep {
+                position: 2,
+                reason: UnsafeReason::StepTooLarge
+            }),
+            dampener.classify(&[1, 2, 7, 8, 9])
+        );
+        assert_eq!(
+            ReportSafety::Dampened {
+                step: UnsafeStep {
+                    position: 3,
+                    reason: UnsafeReason::ZeroStep
+                },
+                removed: vec![2]
+            },
+            dampener.classify(&[8, 6, 4, 4, 1])
+        );
+
+        let dampener = Dampener::new(0, 2..4);
+        assert_eq!(
+            Some(UnsafeReason::StepTooSmall),
+            dampener
+                .find_unsafe_step(&[1, 3, 4])
+                .map(|step| step.reason)
+        );
+
+        // Zero steps are only unsafe when the range does not allow them
+        let dampener = Dampener::new(0, 0..4);
+        assert_eq!(ReportSafety::Safe, dampener.classify(&[3, 3, 4, 4, 6]));
+        assert_eq!(ReportSafety::Safe, dampener.classify(&[5, 5, 5]));
+        assert_eq!(
+            ReportSafety::Unsafe(UnsafeStep {
+                position: 3,
+                reason: UnsafeReason::DirectionChange
+            }),
+            dampener.classify(&[3, 3, 4, 2])
+        );
+    }
+
+    #[test]
+    fn test_example_data() {
+        let data: Vec<String> = "7 6 4 2 1\n1 2 7 8 9\n9 7 6 2 1\n1 3 2 4 5\n8 6 4 4 1\n1 3 6 7 9"
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(vec![2, 4], solve(&data).unwrap());
+    }
+
+    #[test]
+    fn test_dampener() {
+        let dampener = Dampener::new(2, SAFE_STEPS);
+        assert_eq!(Some(vec![1, 2]), dampener.removed_levels(&[1, 9, 9, 2, 3]));
+        assert_eq!(Some(vec![0, 4]), dampener.removed_levels(&[9, 8, 9, 10, 1]));
+        assert_eq!(None, dampener.removed_levels(&[1, 9, 9, 9, 2]));
+        assert_eq!(Some(vec![]), dampener.removed_levels(&[]));
+
+        let dampener = Dampener::new(0, 2..6);
+        assert!(dampener.is_safe(&[1, 3, 8, 10]));
+        assert!(!dampener.is_safe(&[1, 2, 4]));
+    }
+
+    #[test]
+    fn test_dampener_exhaustive() {
+        // Compare with trying every set of removed levels on small reports
+        let mut rng = Rng::new(43);
+        for _ in 0..500 {
+            let report: Vec<i32> = (0..rng.range(0, 9))
+                .map(|_| rng.range(0, 12) as i32)
+                .collect();
+            let fewest = (0u32..1 << report.len())
+                .filter(|removed| {
+                    let kept: Vec<i32> = (0..report.len())
+                        .filter(|i| removed & 1 << i == 0)
+                        .map(|i| report[i])
+                        .collect();
+                    Dampener::new(0, SAFE_STEPS)
+                        .find_unsafe_step(&kept)
+                        .is_none()
+                })
+                .map(u32::count_ones)
+                .min()
+                .unwrap() as usize;
+
+            for max_removals in 0..4 {
+                let removed = Dampener::new(max_removals, SAFE_STEPS).removed_levels(&report);
+                let expected = (fewest <= max_removals).then_some(fewest);
+                assert_eq!(
+                    expected,
+                    removed.as_ref().map(Vec::len),
+                    "Wrong removals for {:?}",
+                    report
+                );
+            }
+        }
+    }
 
     #[test]
     #[cfg(feature = "real_inputs")]
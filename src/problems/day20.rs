@@ -1,16 +1,122 @@
 use hashbrown::{HashMap, HashSet};
-use std::collections::VecDeque;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt,
+};
 
-use crate::{increment_2d_index, Vec2u};
+use crate::{BitGrid, PositionSet, Vec2u};
 
-const DIRECTIONS: &[(isize, isize)] = &[(-1, 0), (1, 0), (0, -1), (0, 1)];
+use super::{
+    geometry::{manhattan_diamond, neighbours_4},
+    input::{
+        check_border, check_grid, check_unique, grid_cells, grid_size, write_grid, Diagnostic,
+        PuzzleInput,
+    },
+    render::{Canvas, Highlight},
+    report::{Report, Section},
+    Variant,
+};
+
+/// The least number of picoseconds a cheat has to save to be counted.
+const MIN_SAVING: usize = 100;
+
+/// The longest cheat for each part, in picoseconds.
+const CHEAT_STEPS: [usize; 2] = [2, 20];
+
+/// The available implementations for day 20.
+pub const VARIANTS: &[Variant] = &[
+    Variant {
+        name: "hashed",
+        solve,
+    },
+    Variant {
+        name: "bitgrid",
+        solve: solve_bitgrid,
+    },
+];
+
+/// The race track, with the walls and the start and end positions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RaceTrack {
+    pub size: Vec2u,
+    pub walls: HashSet<Vec2u>,
+    pub start: Vec2u,
+    pub end: Vec2u,
+}
+
+impl PuzzleInput for RaceTrack {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        let size = grid_size(input_data)?;
+        let mut start = None;
+        let mut end = None;
+        let mut walls = HashSet::new();
+
+        for (pos, c) in grid_cells(input_data) {
+            match c {
+                '#' => {
+                    walls.insert(pos);
+                }
+                'S' => start = Some(pos),
+                'E' => end = Some(pos),
+                '.' => {}
+                _ => return Err(format!("Invalid character {} at {:?}.", c, pos)),
+            }
+        }
+
+        Ok(RaceTrack {
+            size,
+            walls,
+            start: start.ok_or("Failed to find start point")?,
+            end: end.ok_or("Failed to find end point")?,
+        })
+    }
+
+    fn validate(input_data: &[String]) -> Vec<Diagnostic> {
+        let mut diagnostics = check_grid(input_data, 0, |c| "#.SE".contains(c));
+        diagnostics.extend(check_border(input_data, 0, '#'));
+        diagnostics.extend(check_unique(input_data, 0, &['S'], "start"));
+        diagnostics.extend(check_unique(input_data, 0, &['E'], "end"));
+        diagnostics
+    }
+}
+
+impl fmt::Display for RaceTrack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_grid(f, self.size, |pos| {
+            if pos == self.start {
+                'S'
+            } else if pos == self.end {
+                'E'
+            } else if self.walls.contains(&pos) {
+                '#'
+            } else {
+                '.'
+            }
+        })
+    }
+}
 
 /// Solve the problem for day 20, given the provided data.
 pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
-    let (start, _, walls) = parse_maze(input_data)?;
+    let track = RaceTrack::parse_checked(input_data)?;
+    solve_with_walls(&track.start, &track.walls, track.size)
+}
 
-    let cheat_options_part_1 = find_cheat_options(&start, &walls, 100, 2);
-    let cheat_options_part_2 = find_cheat_options(&start, &walls, 100, 20);
+/// Solve the problem for day 20, with the walls stored in a bit grid.
+pub fn solve_bitgrid(input_data: &[String]) -> Result<Vec<u64>, String> {
+    let track = RaceTrack::parse_checked(input_data)?;
+    let walls = BitGrid::from_positions(track.size, track.walls);
+    solve_with_walls(&track.start, &walls, track.size)
+}
+
+/// Solve both parts with any representation of the walls.
+fn solve_with_walls<W: PositionSet>(
+    start: &Vec2u,
+    walls: &W,
+    size: Vec2u,
+) -> Result<Vec<u64>, String> {
+    let cheat_options_part_1 = find_cheat_options(start, walls, size, MIN_SAVING, CHEAT_STEPS[0]);
+    let cheat_options_part_2 = find_cheat_options(start, walls, size, MIN_SAVING, CHEAT_STEPS[1]);
 
     Ok(vec![
         cheat_options_part_1.len() as u64,
@@ -18,62 +124,93 @@ pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
     ])
 }
 
+/// Report how many cheats save each number of picoseconds, for both cheat lengths. Every positive
+/// saving is listed, and the ones of at least `MIN_SAVING` that count for the answers are marked.
+pub fn explain(input_data: &[String]) -> Result<Report, String> {
+    let track = RaceTrack::parse_checked(input_data)?;
+
+    let mut histogram: BTreeMap<usize, [usize; 2]> = BTreeMap::new();
+    for (part, cheat_steps) in CHEAT_STEPS.into_iter().enumerate() {
+        for saving in find_cheat_options(&track.start, &track.walls, track.size, 1, cheat_steps) {
+            histogram.entry(saving).or_default()[part] += 1;
+        }
+    }
+
+    let mut section = Section::new(
+        "Cheat savings",
+        &[
+            "Saving",
+            "Cheats up to 2 ps",
+            "Cheats up to 20 ps",
+            "Counted",
+        ],
+    );
+    for (saving, [short, long]) in histogram {
+        let counted = if saving >= MIN_SAVING { "yes" } else { "no" };
+        section.push_row(vec![
+            saving.into(),
+            short.into(),
+            long.into(),
+            counted.into(),
+        ]);
+    }
+
+    Ok(Report::new(vec![section]))
+}
+
+/// Render the race track with the start and end marked.
+pub fn render(input_data: &[String]) -> Result<Canvas, String> {
+    let RaceTrack {
+        size,
+        walls,
+        start,
+        end,
+    } = RaceTrack::parse_checked(input_data)?;
+    let track = distance_map(&start, &walls, size, usize::MAX);
+
+    let mut canvas = Canvas::from_lines(input_data);
+    canvas.overlay(track.keys().copied(), Highlight::Path);
+    canvas.highlight(start, Highlight::Marker);
+    canvas.highlight(end, Highlight::Marker);
+
+    Ok(canvas)
+}
+
 /// Find possible cheating options
-fn find_cheat_options(
+fn find_cheat_options<W: PositionSet>(
     source: &Vec2u,
-    walls: &HashSet<Vec2u>,
+    walls: &W,
+    size: Vec2u,
     min_length: usize,
     cheat_steps: usize,
 ) -> Vec<usize> {
     // Distance map from start for each pos in track.
-    let start_dist_map = distance_map(source, walls, usize::MAX);
+    let start_dist_map = distance_map(source, walls, size, usize::MAX);
 
     // Find possible cheats for each location along the track.
     start_dist_map
         .iter()
         .flat_map(|(&pos, &start_dist)| {
             let start_dist_map = &start_dist_map;
-            distance_map(&pos, &HashSet::new(), cheat_steps)
-                .into_iter()
-                .filter_map(move |(reachable_pos, steps)| {
-                    let end_dist = start_dist_map
-                        .get(&reachable_pos)
-                        .copied()
-                        .unwrap_or(start_dist + steps);
-                    let cheat_advantage = end_dist.saturating_sub(start_dist + steps);
-                    (cheat_advantage >= min_length).then_some(cheat_advantage)
-                })
+            manhattan_diamond(pos, cheat_steps, size).filter_map(move |(reachable_pos, steps)| {
+                let end_dist = start_dist_map
+                    .get(&reachable_pos)
+                    .copied()
+                    .unwrap_or(start_dist + steps);
+                let cheat_advantage = end_dist.saturating_sub(start_dist + steps);
+                (cheat_advantage >= min_length).then_some(cheat_advantage)
+            })
         })
         .collect()
 }
 
-/// Parse start, end, and wall locations from the map.
-fn parse_maze(input_data: &[String]) -> Result<(Vec2u, Vec2u, HashSet<Vec2u>), String> {
-    let mut start = None;
-    let mut end = None;
-    let mut walls = HashSet::new();
-
-    for (i, s) in input_data.iter().enumerate() {
-        for (j, c) in s.chars().enumerate() {
-            match c {
-                '#' => {
-                    walls.insert((i, j));
-                }
-                'S' => start = Some((i, j)),
-                'E' => end = Some((i, j)),
-                _ => {}
-            }
-        }
-    }
-
-    let start = start.ok_or("Failed to find start point")?;
-    let end = end.ok_or("Failed to find start point")?;
-
-    Ok((start, end, walls))
-}
-
-/// Trace path using bfs search
-fn distance_map(source: &Vec2u, walls: &HashSet<Vec2u>, max_dist: usize) -> HashMap<Vec2u, usize> {
+/// Trace path using bfs search, without leaving the map of the size.
+fn distance_map<W: PositionSet>(
+    source: &Vec2u,
+    walls: &W,
+    size: Vec2u,
+    max_dist: usize,
+) -> HashMap<Vec2u, usize> {
     let mut queue = VecDeque::new();
     let mut distances = HashMap::new();
 
@@ -84,39 +221,35 @@ fn distance_map(source: &Vec2u, walls: &HashSet<Vec2u>, max_dist: usize) -> Hash
         }
 
         distances.insert(pos, dist);
-        queue.extend(step(&(pos, dist), walls, &distances));
+        queue.extend(step(&(pos, dist), walls, size, &distances));
     }
 
     distances
 }
 
-/// Compute valid steps from the current pos
-fn step(
+/// Compute valid steps from the current pos, staying inside the map of the size
+fn step<W: PositionSet>(
     pos: &(Vec2u, usize),
-    walls: &HashSet<Vec2u>,
+    walls: &W,
+    size: Vec2u,
     visited: &HashMap<Vec2u, usize>,
 ) -> Vec<(Vec2u, usize)> {
-    DIRECTIONS
-        .iter()
-        .filter_map(|&next_dir| {
-            increment_2d_index(pos.0 .0, pos.0 .1, next_dir.0, next_dir.1, 1).and_then(|next| {
-                if !(walls.contains(&next) || visited.contains_key(&next)) {
-                    Some((next, pos.1 + 1))
-                } else {
-                    None
-                }
-            })
-        })
+    neighbours_4(pos.0, size)
+        .filter(|next| !(walls.contains(next) || visited.contains_key(next)))
+        .map(|next| (next, pos.1 + 1))
         .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::problems::{
+        input::testing::{assert_text_round_trip, check_round_trips},
+        report::Value,
+    };
 
-    #[test]
-    fn test_example_data() {
-        let map = r"###############
+    // Example data
+    const EXAMPLE: &str = r"###############
 #...#...#.....#
 #.#.#.#.#.###.#
 #S#...#.#.#...#
@@ -132,11 +265,76 @@ mod tests {
 #...#...#...###
 ###############";
 
-        let data: Vec<String> = map.lines().map(String::from).collect();
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<RaceTrack>("#####\n#S#E#\n#.#.#\n#...#\n#####");
+        check_round_trips(20, |rng| {
+            let size = (rng.range(2, 12), rng.range(2, 12));
+            let start = (rng.range(0, size.0), rng.range(0, size.1));
+            let end = loop {
+                let end = (rng.range(0, size.0), rng.range(0, size.1));
+                if end != start {
+                    break end;
+                }
+            };
+            let walls = (0..size.0)
+                .flat_map(|i| (0..size.1).map(move |j| (i, j)))
+                .filter(|&pos| pos != start && pos != end && rng.chance(0.4))
+                .collect();
+            RaceTrack {
+                size,
+                walls,
+                start,
+                end,
+            }
+        });
+    }
 
-        let (start, _, walls) = parse_maze(&data).unwrap();
+    #[test]
+    fn test_validate() {
+        let data: Vec<String> = "#####\n#S.E#\n#S#x#\n####"
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            vec![
+                Diagnostic::at(2, "Invalid character 'x' at column 4.".to_owned()),
+                Diagnostic::at(3, "Row has 4 cells, expected 5.".to_owned()),
+                Diagnostic::at(
+                    2,
+                    "Found another start at column 2, the first is on line 2.".to_owned()
+                ),
+            ],
+            RaceTrack::validate(&data)
+        );
+
+        // Without a wall border the track is rejected, and the search stays inside the map
+        let data = vec!["S.E".to_owned()];
+        assert_eq!(
+            vec![
+                Diagnostic::at(0, "Expected '#' on the edge at column 1.".to_owned()),
+                Diagnostic::at(0, "Expected '#' on the edge at column 2.".to_owned()),
+                Diagnostic::at(0, "Expected '#' on the edge at column 3.".to_owned()),
+            ],
+            RaceTrack::validate(&data)
+        );
+        assert!(solve(&data).is_err());
+        let track = RaceTrack::parse(&data).unwrap();
+        assert_eq!(
+            Vec::<usize>::new(),
+            find_cheat_options(&track.start, &track.walls, track.size, 1, 2)
+        );
+    }
+
+    #[test]
+    fn test_example_data() {
+        let data: Vec<String> = EXAMPLE.lines().map(String::from).collect();
 
-        let cheat_options_part_1 = find_cheat_options(&start, &walls, 1, 2);
+        let RaceTrack {
+            size, walls, start, ..
+        } = RaceTrack::parse(&data).unwrap();
+
+        let cheat_options_part_1 = find_cheat_options(&start, &walls, size, 1, 2);
         assert_eq!(
             44,
             cheat_options_part_1.len(),
@@ -144,13 +342,36 @@ mod tests {
             cheat_options_part_1.len()
         );
 
-        let cheat_options_part_2 = find_cheat_options(&start, &walls, 50, 20);
+        let cheat_options_part_2 = find_cheat_options(&start, &walls, size, 50, 20);
         assert_eq!(
             285,
             cheat_options_part_2.len(),
             "Result for part 2 example should be 285 but was {}",
             cheat_options_part_2.len()
         );
+
+        let walls = BitGrid::from_positions(size, walls);
+        assert_eq!(44, find_cheat_options(&start, &walls, size, 1, 2).len());
+        assert_eq!(285, find_cheat_options(&start, &walls, size, 50, 20).len());
+    }
+
+    #[test]
+    fn test_explain() {
+        let data: Vec<String> = EXAMPLE.lines().map(String::from).collect();
+        let report = explain(&data).unwrap();
+        let rows = &report.sections[0].rows;
+
+        // Savings below the threshold are listed too, but not counted
+        assert_eq!(
+            vec![Value::from(2), 14.into(), 138.into(), "no".into()],
+            rows[0]
+        );
+        let row = rows.iter().find(|row| row[0] == Value::from(64)).unwrap();
+        assert_eq!(
+            vec![Value::from(64), 1.into(), 19.into(), "no".into()],
+            *row
+        );
+        assert_eq!(Value::from(76), rows.last().unwrap()[0]);
     }
 
     #[test]
@@ -1,117 +1,469 @@
-use regex::Regex;
+use std::fmt;
 
-const OPERATION_PATTERN: &str = r"mul\(\d+,\d+\)";
-const DO_PATTERN: &str = r"do\(\)";
-const DONT_PATTERN: &str = r"don't\(\)";
+use hashbrown::HashMap;
+
+use super::{
+    input::PuzzleInput,
+    report::{Report, Section},
+};
+
+/// The instructions of the corrupted memory, with the number of operands they take.
+pub const KEYWORDS: &[Keyword] = &[
+    Keyword {
+        name: "mul",
+        arity: 2,
+    },
+    Keyword {
+        name: "do",
+        arity: 0,
+    },
+    Keyword {
+        name: "don't",
+        arity: 0,
+    },
+];
+
+/// The corrupted memory of the computer, as lines of text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CorruptedMemory {
+    pub lines: Vec<String>,
+}
+
+impl PuzzleInput for CorruptedMemory {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        Ok(CorruptedMemory {
+            lines: input_data.to_vec(),
+        })
+    }
+}
+
+impl fmt::Display for CorruptedMemory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
 
 /// Solve the problem for day three, given the provided data.
 pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
     // Merge rows into single string
-    let full_data = input_data.join("");
+    let full_data = CorruptedMemory::parse_checked(input_data)?.lines.join("");
+    let tokens = Scanner::new(KEYWORDS).scan(&full_data);
 
     // Part 1
-    let valid_operations = find_valid_operations(&full_data);
-    let result_part_1 = execute_all_operations(&valid_operations)?;
+    let result_part_1 = Interpreter::new()
+        .with_handler("mul", multiply)
+        .run(&tokens)?;
 
     // Part 2
-    let operations_and_triggers = find_operations_and_triggers(&full_data);
-    let result_part_2 = execute_enabled_operations(&operations_and_triggers)?;
+    let result_part_2 = conditional_interpreter().run(&tokens)?;
 
     Ok(vec![result_part_1, result_part_2])
 }
 
-// Filter the input data and find all valid operations
-fn find_valid_operations(input_data: &str) -> Vec<&str> {
-    let re =
-        Regex::new(OPERATION_PATTERN).expect("Should be able to create a regex from the pattern.");
-    let mut all_ops = Vec::new();
-    all_ops.extend(re.find_iter(input_data).map(|m| m.as_str()));
+/// Trace the instructions of part 2, with their position in the input, whether they were enabled
+/// and their contribution, and the transitions of the state for `do()` and `don't()`.
+pub fn explain(input_data: &[String]) -> Result<Report, String> {
+    let lines = CorruptedMemory::parse_checked(input_data)?.lines;
+    let tokens = Scanner::new(KEYWORDS).scan(&lines.join(""));
+
+    // The offset of the start of each line in the joined lines
+    let line_starts: Vec<usize> = lines
+        .iter()
+        .scan(0, |start, line| {
+            let line_start = *start;
+            *start += line.len();
+            Some(line_start)
+        })
+        .collect();
+
+    let mut trace = Section::new(
+        "Trace",
+        &[
+            "Line",
+            "Column",
+            "Instruction",
+            "Enabled",
+            "Contribution",
+            "Transition",
+        ],
+    );
+    for step in conditional_interpreter().trace(&tokens)? {
+        // The last line starting at or before the offset, skipping empty lines
+        let line = line_starts.partition_point(|&start| start <= step.offset) - 1;
+        let state = |enabled| if enabled { "enabled" } else { "disabled" };
+        let transition = match step.token.name {
+            "mul" => None,
+            _ => Some(format!(
+                "{} -> {}",
+                state(step.enabled_before),
+                state(step.enabled_after)
+            )),
+        };
+        trace.push_row(vec![
+            (line + 1).into(),
+            (step.offset - line_starts[line] + 1).into(),
+            step.token.to_string().into(),
+            if step.enabled_before { "yes" } else { "no" }.into(),
+            step.contribution.into(),
+            transition.into(),
+        ]);
+    }
+
+    Ok(Report::new(vec![trace]))
+}
+
+/// The interpreter of part 2, where `do()` and `don't()` enable and disable the instructions.
+fn conditional_interpreter() -> Interpreter {
+    Interpreter::new()
+        .with_handler("mul", multiply)
+        .with_handler("do", enable)
+        .with_handler("don't", disable)
+}
+
+/// An instruction found in the corrupted memory, with the keyword name and its operands.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token {
+    pub name: &'static str,
+    pub args: Vec<u64>,
+}
 
-    all_ops
+impl Token {
+    pub fn new(name: &'static str, args: Vec<u64>) -> Token {
+        Token { name, args }
+    }
 }
 
-// Execute a list of operations defined as &str
-fn execute_all_operations(ops: &[&str]) -> Result<u64, String> {
-    let mut result = 0;
-    for operation in ops {
-        result += execute_single_from_string(operation)?;
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let args: Vec<String> = self.args.iter().map(u64::to_string).collect();
+        write!(f, "{}({})", self.name, args.join(","))
     }
-    Ok(result)
 }
 
-// Execute the enabled instruction based on the embedded trigger instructions
-fn execute_enabled_operations(instructions: &[&str]) -> Result<u64, String> {
-    let mut result = 0;
-    let mut enabled = true;
-    for &instruction in instructions {
-        match instruction {
-            "don't()" => enabled = false,
-            "do()" => enabled = true,
-            &_ => {
-                if enabled {
-                    result += execute_single_from_string(instruction)?;
+/// The syntax of an instruction, written as the name followed by the operands in parentheses,
+/// separated by commas.
+#[derive(Clone, Copy)]
+pub struct Keyword {
+    pub name: &'static str,
+    pub arity: usize,
+}
+
+/// Scan text for the instructions of the keywords in a single pass, skipping everything else.
+#[derive(Clone, Copy)]
+pub struct Scanner<'a> {
+    keywords: &'a [Keyword],
+    max_digits: Option<usize>,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(keywords: &'a [Keyword]) -> Scanner<'a> {
+        Scanner {
+            keywords,
+            max_digits: None,
+        }
+    }
+
+    /// Only accept operands of at most this many digits.
+    pub fn with_max_digits(mut self, max_digits: usize) -> Scanner<'a> {
+        self.max_digits = Some(max_digits);
+        self
+    }
+
+    /// Find all instructions in the text, with the byte offset they start at.
+    pub fn scan(&self, text: &str) -> Vec<(usize, Token)> {
+        let bytes = text.as_bytes();
+        let mut tokens = Vec::new();
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let found = self
+                .keywords
+                .iter()
+                .find_map(|keyword| self.match_keyword(keyword, &bytes[offset..]));
+            match found {
+                Some((token, len)) => {
+                    tokens.push((offset, token));
+                    offset += len;
                 }
+                None => offset += 1,
             }
         }
+
+        tokens
     }
-    Ok(result)
-}
 
-// Parse a single operation from text format into the values and compute the result
-fn execute_single_from_string(operation: &str) -> Result<u64, String> {
-    // Extract the parenthesis bit
-    let operation_values = &operation[3..];
-    let split: Vec<&str> = operation_values.split(",").collect();
+    /// Match the instruction at the start of the bytes, returning the token and its length.
+    fn match_keyword(&self, keyword: &Keyword, bytes: &[u8]) -> Option<(Token, usize)> {
+        let mut pos = keyword.name.len();
+        if !bytes.starts_with(keyword.name.as_bytes()) || bytes.get(pos) != Some(&b'(') {
+            return None;
+        }
+        pos += 1;
+
+        let mut args = Vec::with_capacity(keyword.arity);
+        for i in 0..keyword.arity {
+            if i > 0 {
+                if bytes.get(pos) != Some(&b',') {
+                    return None;
+                }
+                pos += 1;
+            }
+            let (arg, len) = self.match_number(&bytes[pos..])?;
+            args.push(arg);
+            pos += len;
+        }
+        if bytes.get(pos) != Some(&b')') {
+            return None;
+        }
 
-    if split.len() != 2 {
-        return Err(format!(
-            "Failed to parse operation values from {}",
-            operation_values
-        ));
+        Some((Token::new(keyword.name, args), pos + 1))
     }
 
-    // Parse the strings into integers
-    let first_number = split[0][1..]
-        .parse::<u64>()
-        .map_err(|_| format!("Failed to parse {} as u32.", split[0]))?;
+    /// Match a number at the start of the bytes, returning the number and its number of digits.
+    fn match_number(&self, bytes: &[u8]) -> Option<(u64, usize)> {
+        let len = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+        if len == 0 || self.max_digits.is_some_and(|max| len > max) {
+            return None;
+        }
 
-    let second_number = &split[1][..split[1].len() - 1]
-        .parse::<u64>()
-        .map_err(|_| format!("Failed to parse {} as u32.", split[0]))?;
+        bytes[..len]
+            .iter()
+            .try_fold(0u64, |val, &b| {
+                val.checked_mul(10)?.checked_add((b - b'0') as u64)
+            })
+            .map(|val| (val, len))
+    }
+}
 
-    Ok(first_number * second_number)
+/// The state of the computer while running the instructions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Machine {
+    pub enabled: bool,
+    pub total: u64,
 }
 
-// Find all the matches for either operations or do / don't triggers
-fn find_operations_and_triggers(input_data: &str) -> Vec<&str> {
-    // Define all relevant regex patterns
-    let main_pattern =
-        Regex::new(OPERATION_PATTERN).expect("Should be able to create the regex pattern.");
-    let do_pattern = Regex::new(DO_PATTERN).expect("Should be able to create the regex pattern.");
-    let dont_pattern =
-        Regex::new(DONT_PATTERN).expect("Should be able to create the regex pattern.");
+/// Execute a single instruction on the machine.
+pub type Handler = fn(&mut Machine, &Token) -> Result<(), String>;
+
+/// Run instructions with a handler for each keyword. Instructions without a handler are skipped.
+#[derive(Clone, Default)]
+pub struct Interpreter {
+    handlers: HashMap<&'static str, Handler>,
+}
+
+impl Interpreter {
+    pub fn new() -> Interpreter {
+        Interpreter::default()
+    }
+
+    /// Handle the instructions of the keyword with the handler, replacing any previous one.
+    pub fn with_handler(mut self, name: &'static str, handler: Handler) -> Interpreter {
+        self.handlers.insert(name, handler);
+        self
+    }
 
-    // Collect all matches
-    let mut all_matches: Vec<(usize, &str)> = Vec::new();
+    /// Run the instructions on a new machine, returning the total.
+    pub fn run(&self, tokens: &[(usize, Token)]) -> Result<u64, String> {
+        self.run_with(tokens, |_| ())
+    }
 
-    for mat in main_pattern.find_iter(input_data) {
-        all_matches.push((mat.start(), mat.as_str()));
+    /// Run the instructions on a new machine, recording the effect of each handled instruction.
+    pub fn trace(&self, tokens: &[(usize, Token)]) -> Result<Vec<TraceStep>, String> {
+        let mut steps = Vec::new();
+        self.run_with(tokens, |step| steps.push(step))?;
+        Ok(steps)
     }
-    for mat in do_pattern.find_iter(input_data) {
-        all_matches.push((mat.start(), mat.as_str()));
+
+    fn run_with<F: FnMut(TraceStep)>(
+        &self,
+        tokens: &[(usize, Token)],
+        mut on_step: F,
+    ) -> Result<u64, String> {
+        let mut machine = Machine {
+            enabled: true,
+            total: 0,
+        };
+        for (offset, token) in tokens {
+            if let Some(handler) = self.handlers.get(token.name) {
+                let before = machine.clone();
+                handler(&mut machine, token)
+                    .map_err(|err| format!("Offset {}: {}", offset, err))?;
+                on_step(TraceStep {
+                    offset: *offset,
+                    token: token.clone(),
+                    enabled_before: before.enabled,
+                    enabled_after: machine.enabled,
+                    contribution: machine.total - before.total,
+                });
+            }
+        }
+
+        Ok(machine.total)
     }
-    for mat in dont_pattern.find_iter(input_data) {
-        all_matches.push((mat.start(), mat.as_str()));
+}
+
+/// The effect of a single instruction on the machine.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceStep {
+    pub offset: usize,
+    pub token: Token,
+    pub enabled_before: bool,
+    pub enabled_after: bool,
+    pub contribution: u64,
+}
+
+/// Add the product of the operands to the total, if the machine is enabled.
+fn multiply(machine: &mut Machine, token: &Token) -> Result<(), String> {
+    let [a, b] = token.args[..] else {
+        return Err(format!("Expected 2 operands for {}.", token));
+    };
+    if machine.enabled {
+        machine.total = a
+            .checked_mul(b)
+            .and_then(|product| machine.total.checked_add(product))
+            .ok_or("The total is too large to fit in u64.")?;
     }
+    Ok(())
+}
 
-    // Sort the matches by their start position
-    all_matches.sort_by_key(|&(pos, _)| pos);
+fn enable(machine: &mut Machine, _: &Token) -> Result<(), String> {
+    machine.enabled = true;
+    Ok(())
+}
 
-    all_matches.iter().map(|item| item.1).collect()
+fn disable(machine: &mut Machine, _: &Token) -> Result<(), String> {
+    machine.enabled = false;
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::problems::{
+        input::testing::{assert_text_round_trip, check_round_trips},
+        report::Value,
+    };
+
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<CorruptedMemory>(
+            "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))",
+        );
+        check_round_trips(3, |rng| CorruptedMemory {
+            lines: (0..rng.range(0, 5))
+                .map(|_| {
+                    (0..rng.range(1, 40))
+                        .map(|_| rng.choose(&['m', 'u', 'l', '(', ')', ',', '1', '7', 'd', '\'']))
+                        .collect()
+                })
+                .collect(),
+        });
+    }
+
+    #[test]
+    fn test_example_data() {
+        let data = vec![
+            "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))".to_owned(),
+        ];
+        assert_eq!(vec![161, 48], solve(&data).unwrap());
+    }
+
+    #[test]
+    fn test_scanner() {
+        let text = "mul(1,22)do()mul(4444,5)don't()mul(6,7,8)mul(9,)mmul(2,3)";
+        let tokens = Scanner::new(KEYWORDS).scan(text);
+        assert_eq!(
+            vec![
+                (0, Token::new("mul", vec![1, 22])),
+                (9, Token::new("do", vec![])),
+                (13, Token::new("mul", vec![4444, 5])),
+                (24, Token::new("don't", vec![])),
+                (49, Token::new("mul", vec![2, 3]))
+            ],
+            tokens
+        );
+
+        let tokens = Scanner::new(KEYWORDS).with_max_digits(3).scan(text);
+        assert!(!tokens.contains(&(13, Token::new("mul", vec![4444, 5]))));
+        assert_eq!(4, tokens.len());
+
+        // A new instruction only needs a keyword
+        let keywords = [Keyword {
+            name: "add",
+            arity: 3,
+        }];
+        assert_eq!(
+            vec![(4, Token::new("add", vec![1, 2, 3]))],
+            Scanner::new(&keywords).scan("add(add(1,2,3)add(4,5)")
+        );
+    }
+
+    #[test]
+    fn test_interpreter() {
+        let tokens = Scanner::new(KEYWORDS).scan("mul(2,3)don't()mul(4,5)do()");
+
+        // Handlers can be replaced, such as counting the instructions instead
+        let count = Interpreter::new()
+            .with_handler("mul", |machine, _| {
+                machine.total += 1;
+                Ok(())
+            })
+            .with_handler("do", |machine, _| {
+                machine.total += 1;
+                Ok(())
+            });
+        assert_eq!(Ok(3), count.run(&tokens));
+
+        let overflow = Scanner::new(KEYWORDS).scan("mul(4294967296,4294967296)");
+        assert!(Interpreter::new()
+            .with_handler("mul", multiply)
+            .run(&overflow)
+            .is_err());
+
+        let unary = [Keyword {
+            name: "mul",
+            arity: 1,
+        }];
+        let tokens = Scanner::new(&unary).scan("mul(7)");
+        assert_eq!(
+            Err("Offset 0: Expected 2 operands for mul(7).".to_owned()),
+            Interpreter::new()
+                .with_handler("mul", multiply)
+                .run(&tokens)
+        );
+    }
+
+    #[test]
+    fn test_explain() {
+        let data = vec![
+            "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](".to_owned(),
+            String::new(),
+            "mul(11,8)undo()?mul(8,5))".to_owned(),
+        ];
+        let report = explain(&data).unwrap();
+        let rows: Vec<String> = report.sections[0]
+            .rows
+            .iter()
+            .map(|row| {
+                let row: Vec<String> = row.iter().map(Value::to_string).collect();
+                row.join(" ")
+            })
+            .collect();
+
+        assert_eq!(
+            vec![
+                "1 2 mul(2,4) yes 8 -",
+                "1 21 don't() yes 0 enabled -> disabled",
+                "1 29 mul(5,5) no 0 -",
+                "3 1 mul(11,8) no 0 -",
+                "3 12 do() no 0 disabled -> enabled",
+                "3 17 mul(8,5) yes 40 -",
+            ],
+            rows
+        );
+    }
 
     #[test]
     #[cfg(feature = "real_inputs")]
@@ -1,128 +1,371 @@
-use super::utils;
+use std::fmt;
 
-const PATTERN: &str = "MAS";
+use hashbrown::HashSet;
+
+use crate::{Vec2i, Vec2u};
+
+use super::{
+    geometry::{step_in_bounds, DIRECTIONS_4, DIRECTIONS_8},
+    input::{check_grid, Diagnostic, PuzzleInput},
+    render::{Canvas, Highlight},
+};
+
+/// The word to find in part 1.
+const WORD: &str = "XMAS";
+
+/// The cross of two diagonal words "MAS" to find in part 2.
+const X_MAS: &str = "M.S\n.A.\nM.S";
+
+/// Diagonal directions, clockwise starting at down-right.
+const DIAGONALS: [Vec2i; 4] = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
+
+/// Directions reading left to right or top to bottom: right, down-right, down and up-right.
+const FORWARD: [Vec2i; 4] = [(0, 1), (1, 1), (1, 0), (-1, 1)];
+
+/// The word search puzzle, as rows of letters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WordSearch {
+    pub rows: Vec<String>,
+}
+
+impl PuzzleInput for WordSearch {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        Ok(WordSearch {
+            rows: input_data.to_vec(),
+        })
+    }
+
+    fn validate(input_data: &[String]) -> Vec<Diagnostic> {
+        check_grid(input_data, 0, |c| c.is_ascii_alphabetic())
+    }
+}
+
+impl fmt::Display for WordSearch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in &self.rows {
+            writeln!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
+
+impl WordSearch {
+    /// The size of the grid as (rows, cols).
+    pub fn size(&self) -> Vec2u {
+        (
+            self.rows.len(),
+            self.rows.first().map_or(0, |row| row.len()),
+        )
+    }
+
+    /// The letter at a position, if inside the grid.
+    fn letter(&self, pos: Vec2u) -> Option<u8> {
+        self.rows.get(pos.0)?.as_bytes().get(pos.1).copied()
+    }
+}
 
 /// Solve the problem for day four, given the provided data.
 pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
-    let result_part_1 = count_all_xmas_seq(input_data);
-    let result_part_2 = count_all_xmas_cross(input_data);
+    let grid = WordSearch::parse_checked(input_data)?;
+    let result_part_1 = WordFinder::new(&[WORD]).count(&grid) as u64;
+    let result_part_2 = Stencil::parse(X_MAS)?.count(&grid) as u64;
 
     Ok(vec![result_part_1, result_part_2])
 }
 
-/// Count all backwards and forwards instances of *XMAS*
-fn count_all_xmas_seq(data: &[String]) -> u64 {
-    let mut count = 0;
-    for i in 0..data.len() {
-        for j in 0..data[i].len() {
-            count += count_matches_at(i, j, data);
+/// Render the grid with only the letters of the words of part 1.
+pub fn render(input_data: &[String]) -> Result<Canvas, String> {
+    let grid = WordSearch::parse_checked(input_data)?;
+    let cells: HashSet<Vec2u> = WordFinder::new(&[WORD])
+        .find(&grid)
+        .into_iter()
+        .flat_map(|found| found.cells)
+        .collect();
+
+    let mut canvas = Canvas::from_lines(&grid.rows);
+    let (num_rows, num_cols) = canvas.size();
+    for i in 0..num_rows {
+        for j in 0..num_cols {
+            if !cells.contains(&(i, j)) {
+                canvas.set_char((i, j), '.');
+            }
         }
     }
+    canvas.overlay(cells, Highlight::Path);
 
-    count
+    Ok(canvas)
 }
 
-/// Count all locations with a MAS cross
-fn count_all_xmas_cross(data: &[String]) -> u64 {
-    let mut count = 0;
-    for i in 0..data.len() {
-        for j in 0..data[i].len() {
-            if has_cross_at(i, j, data) {
-                count += 1;
-            }
+/// The directions words can be read in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DirectionSet {
+    Orthogonal,
+    Diagonal,
+    All,
+    /// Only reading left to right or top to bottom, never backwards.
+    Forward,
+}
+
+impl DirectionSet {
+    pub fn directions(&self) -> &'static [Vec2i] {
+        match self {
+            DirectionSet::Orthogonal => &DIRECTIONS_4,
+            DirectionSet::Diagonal => &DIAGONALS,
+            DirectionSet::All => &DIRECTIONS_8,
+            DirectionSet::Forward => &FORWARD,
         }
     }
+}
 
-    count
+/// A word found in the grid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WordMatch {
+    /// The index of the word in the searched words.
+    pub word: usize,
+    pub start: Vec2u,
+    /// The direction the word is read in, or [`None`] for a single letter.
+    pub dir: Option<Vec2i>,
+    /// The cells of the letters, in the order of the word.
+    pub cells: Vec<Vec2u>,
 }
 
-/// Check for matches starting at a certain index
-fn count_matches_at(i: usize, j: usize, data: &[String]) -> u64 {
-    let char = utils::get_char(data, i, j);
-    let mut count = 0;
-    if let Some(char) = char {
-        if char == 'X' {
-            count += find_sequence_match_at(i, j, 0, 1, data);
-            count += find_sequence_match_at(i, j, 1, 1, data);
-            count += find_sequence_match_at(i, j, 1, 0, data);
-            count += find_sequence_match_at(i, j, 1, -1, data);
-            count += find_sequence_match_at(i, j, 0, -1, data);
-            count += find_sequence_match_at(i, j, -1, -1, data);
-            count += find_sequence_match_at(i, j, -1, 0, data);
-            count += find_sequence_match_at(i, j, -1, 1, data);
+/// Search a grid for a list of words, read in a set of directions.
+#[derive(Clone, Copy, Debug)]
+pub struct WordFinder<'a> {
+    words: &'a [&'a str],
+    directions: DirectionSet,
+    wrap: bool,
+}
+
+impl<'a> WordFinder<'a> {
+    /// Create a finder for the words in all directions, without wrapping around the edges.
+    pub fn new(words: &'a [&'a str]) -> WordFinder<'a> {
+        WordFinder {
+            words,
+            directions: DirectionSet::All,
+            wrap: false,
         }
     }
 
-    count
-}
-
-/// Check if a location that contains X or S has a sequence that matches XMAS or SAMX.
-fn find_sequence_match_at(
-    i: usize,
-    j: usize,
-    increment_i: isize,
-    increment_j: isize,
-    data: &[String],
-) -> u64 {
-    for (index, c) in PATTERN.chars().enumerate() {
-        let (i, j) = match utils::increment_2d_index(i, j, increment_i, increment_j, index + 1) {
-            Some(value) => value,
-            None => return 0,
-        };
-        if !is_location_matching(i, j, data, c) {
-            // The squence is broken, return
-            return 0;
+    pub fn with_directions(mut self, directions: DirectionSet) -> WordFinder<'a> {
+        self.directions = directions;
+        self
+    }
+
+    /// Continue words leaving the grid on the opposite edge.
+    pub fn with_wrap(mut self) -> WordFinder<'a> {
+        self.wrap = true;
+        self
+    }
+
+    /// Find every occurrence of the words, ordered by the start cell, direction and word.
+    /// Single letters are found once, without a direction, before the longer words, and empty
+    /// words are never found.
+    pub fn find(&self, grid: &WordSearch) -> Vec<WordMatch> {
+        let size = grid.size();
+        let mut matches = Vec::new();
+        for i in 0..size.0 {
+            for j in 0..size.1 {
+                for (word, text) in self.words.iter().enumerate() {
+                    if text.len() == 1 {
+                        if let Some(cells) = self.match_word(grid, text, (i, j), (0, 0)) {
+                            matches.push(WordMatch {
+                                word,
+                                start: (i, j),
+                                dir: None,
+                                cells,
+                            });
+                        }
+                    }
+                }
+                for &dir in self.directions.directions() {
+                    for (word, text) in self.words.iter().enumerate() {
+                        if text.len() < 2 {
+                            continue;
+                        }
+                        if let Some(cells) = self.match_word(grid, text, (i, j), dir) {
+                            matches.push(WordMatch {
+                                word,
+                                start: (i, j),
+                                dir: Some(dir),
+                                cells,
+                            });
+                        }
+                    }
+                }
+            }
         }
+
+        matches
     }
 
-    1
-}
+    /// Count every occurrence of the words.
+    pub fn count(&self, grid: &WordSearch) -> usize {
+        self.find(grid).len()
+    }
 
-/// Check if there is a cross at the location
-fn has_cross_at(i: usize, j: usize, data: &[String]) -> bool {
-    if is_location_matching(i, j, data, 'A') {
-        return check_adjacent_for_cross(i, j, data);
+    /// Match the word from the start in the direction, returning the cells of its letters.
+    fn match_word(
+        &self,
+        grid: &WordSearch,
+        word: &str,
+        start: Vec2u,
+        dir: Vec2i,
+    ) -> Option<Vec<Vec2u>> {
+        let size = grid.size();
+        let mut cells = Vec::with_capacity(word.len());
+        let mut pos = start;
+        for (k, letter) in word.bytes().enumerate() {
+            if k > 0 {
+                pos = if self.wrap {
+                    wrapping_step(pos, dir, size)
+                } else {
+                    step_in_bounds(pos, dir, size)?
+                };
+            }
+            if grid.letter(pos) != Some(letter) {
+                return None;
+            }
+            cells.push(pos);
+        }
+
+        Some(cells)
     }
+}
 
-    false
+/// Take a single step in the direction, continuing on the opposite edge when leaving the bounds.
+fn wrapping_step(pos: Vec2u, dir: Vec2i, bounds: Vec2u) -> Vec2u {
+    let wrap = |val: usize, step: isize, bound: usize| {
+        (val as isize + step).rem_euclid(bound as isize) as usize
+    };
+    (wrap(pos.0, dir.0, bounds.0), wrap(pos.1, dir.1, bounds.1))
 }
 
-/// Check both diagonals at the location for a valid M*S or S*M sequence.
-fn check_adjacent_for_cross(i: usize, j: usize, data: &[String]) -> bool {
-    check_diagonal(i, j, data, true) && check_diagonal(i, j, data, false)
+/// A small grid of letters to find in any orientation, where `.` matches any letter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stencil {
+    pub size: Vec2u,
+    /// The letters with their offset from the top left corner, sorted by the offset.
+    pub letters: Vec<(Vec2u, u8)>,
 }
 
-/// Check the diagonals of a cross, to check is they match the M*S or S*M pattern
-fn check_diagonal(i: usize, j: usize, data: &[String], up: bool) -> bool {
-    let incr_i_1 = if up { -1 } else { 1 };
-    let (i_1, j_1) = match utils::increment_2d_index(i, j, incr_i_1, -1, 1) {
-        Some(value) => value,
-        None => return false,
-    };
+impl Stencil {
+    /// Parse a stencil from rows of equal length, separated by new lines.
+    pub fn parse(pattern: &str) -> Result<Stencil, String> {
+        let rows: Vec<&str> = pattern.lines().collect();
+        let num_cols = rows.first().map_or(0, |row| row.len());
+        if num_cols == 0 || rows.iter().any(|row| row.len() != num_cols) {
+            return Err(format!(
+                "Expected rows of equal length in the stencil {:?}.",
+                pattern
+            ));
+        }
 
-    let incr_i_2 = if up { 1 } else { -1 };
-    let (i_2, j_2) = match utils::increment_2d_index(i, j, incr_i_2, 1, 1) {
-        Some(value) => value,
-        None => return false,
-    };
+        let letters = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(i, row)| row.bytes().enumerate().map(move |(j, b)| ((i, j), b)))
+            .filter(|&(_, b)| b != b'.')
+            .collect();
 
-    if is_location_matching(i_1, j_1, data, 'M') {
-        return is_location_matching(i_2, j_2, data, 'S');
-    } else if is_location_matching(i_1, j_1, data, 'S') {
-        return is_location_matching(i_2, j_2, data, 'M');
+        Ok(Stencil {
+            size: (rows.len(), num_cols),
+            letters,
+        })
     }
 
-    false
-}
+    /// The distinct stencils under the four rotations, with and without reflection.
+    pub fn orientations(&self) -> Vec<Stencil> {
+        let mut orientations: Vec<Stencil> = Vec::with_capacity(8);
+        let mut current = self.clone();
+        for _ in 0..4 {
+            for stencil in [current.reflected(), current.clone()] {
+                if !orientations.contains(&stencil) {
+                    orientations.push(stencil);
+                }
+            }
+            current = current.rotated();
+        }
+
+        orientations
+    }
+
+    /// Rotate the stencil clockwise.
+    fn rotated(&self) -> Stencil {
+        let (num_rows, num_cols) = self.size;
+        Stencil::from_letters(
+            (num_cols, num_rows),
+            self.letters
+                .iter()
+                .map(|&((i, j), b)| ((j, num_rows - 1 - i), b)),
+        )
+    }
 
-/// Check if the location at index i, j matches a certain char
-fn is_location_matching(i: usize, j: usize, data: &[String], ref_char: char) -> bool {
-    if let Some(char) = utils::get_char(data, i, j) {
-        if char == ref_char {
-            return true;
+    /// Reflect the stencil left to right.
+    fn reflected(&self) -> Stencil {
+        let num_cols = self.size.1;
+        Stencil::from_letters(
+            self.size,
+            self.letters
+                .iter()
+                .map(|&((i, j), b)| ((i, num_cols - 1 - j), b)),
+        )
+    }
+
+    fn from_letters<I: Iterator<Item = (Vec2u, u8)>>(size: Vec2u, letters: I) -> Stencil {
+        let mut letters: Vec<(Vec2u, u8)> = letters.collect();
+        letters.sort();
+        Stencil { size, letters }
+    }
+
+    /// Find the occurrences of the stencil in any orientation, ordered by the top left corner.
+    pub fn find(&self, grid: &WordSearch) -> Vec<StencilMatch> {
+        let orientations = self.orientations();
+        let size = grid.size();
+        let mut matches = Vec::new();
+        for i in 0..size.0 {
+            for j in 0..size.1 {
+                for (orientation, stencil) in orientations.iter().enumerate() {
+                    if i + stencil.size.0 > size.0 || j + stencil.size.1 > size.1 {
+                        continue;
+                    }
+                    if stencil
+                        .letters
+                        .iter()
+                        .all(|&((d_i, d_j), b)| grid.letter((i + d_i, j + d_j)) == Some(b))
+                    {
+                        matches.push(StencilMatch {
+                            start: (i, j),
+                            orientation,
+                            cells: stencil
+                                .letters
+                                .iter()
+                                .map(|&((d_i, d_j), _)| (i + d_i, j + d_j))
+                                .collect(),
+                        });
+                    }
+                }
+            }
         }
+
+        matches
+    }
+
+    /// Count the occurrences of the stencil in any orientation.
+    pub fn count(&self, grid: &WordSearch) -> usize {
+        self.find(grid).len()
     }
-    false
+}
+
+/// A stencil found in the grid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StencilMatch {
+    /// The top left corner of the stencil.
+    pub start: Vec2u,
+    /// The index of the orientation, as given by [`Stencil::orientations`].
+    pub orientation: usize,
+    /// The cells of the letters of the stencil, skipping the wildcards.
+    pub cells: Vec<Vec2u>,
 }
 
 #[cfg(test)]
@@ -130,6 +373,36 @@ mod tests {
     use std::str::FromStr;
 
     use super::*;
+    use crate::problems::input::testing::{assert_text_round_trip, check_round_trips};
+
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<WordSearch>("MMMSXXMASM\nMSAMXMSMSA\nAMXSXMAAMM");
+        check_round_trips(4, |rng| {
+            let cols = rng.range(1, 12);
+            WordSearch {
+                rows: (0..rng.range(1, 12))
+                    .map(|_| {
+                        (0..cols)
+                            .map(|_| rng.choose(&['X', 'M', 'A', 'S']))
+                            .collect()
+                    })
+                    .collect(),
+            }
+        });
+    }
+
+    #[test]
+    fn test_validate() {
+        let data: Vec<String> = "XMAS\nMX1S\nXMA".lines().map(String::from).collect();
+        assert_eq!(
+            vec![
+                Diagnostic::at(1, "Invalid character '1' at column 3.".to_owned()),
+                Diagnostic::at(2, "Row has 3 cells, expected 4.".to_owned()),
+            ],
+            WordSearch::validate(&data)
+        );
+    }
 
     #[test]
     fn test_example_data() {
@@ -160,6 +433,82 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_word_finder() {
+        let data: Vec<String> = "ATXC\nXAXX\nTCAT".lines().map(String::from).collect();
+        let grid = WordSearch::parse(&data).unwrap();
+        let words = ["CAT", "AT"];
+
+        let found = WordFinder::new(&words)
+            .with_directions(DirectionSet::Forward)
+            .find(&grid);
+        let starts: Vec<(usize, Vec2u, Option<Vec2i>)> = found
+            .iter()
+            .map(|found| (found.word, found.start, found.dir))
+            .collect();
+        assert_eq!(
+            vec![
+                (1, (0, 0), Some((0, 1))),
+                (0, (2, 1), Some((0, 1))),
+                (1, (2, 2), Some((0, 1)))
+            ],
+            starts
+        );
+        assert_eq!(vec![(2, 1), (2, 2), (2, 3)], found[1].cells);
+
+        let finder = WordFinder::new(&words).with_directions(DirectionSet::Diagonal);
+        assert_eq!(1, finder.count(&grid));
+        assert_eq!(6, WordFinder::new(&words).count(&grid));
+
+        // Wrapping around the edges also finds "CAT" from the end of the first row
+        let finder = WordFinder::new(&words[..1])
+            .with_directions(DirectionSet::Orthogonal)
+            .with_wrap();
+        let found = finder.find(&grid);
+        assert_eq!(3, found.len());
+        assert_eq!(vec![(0, 3), (0, 0), (0, 1)], found[0].cells);
+
+        // Single letters are found once, not once per direction
+        let words = ["C", "", "TC"];
+        let found = WordFinder::new(&words).find(&grid);
+        let starts: Vec<(usize, Vec2u, Option<Vec2i>)> = found
+            .iter()
+            .map(|found| (found.word, found.start, found.dir))
+            .collect();
+        assert_eq!(
+            vec![
+                (0, (0, 3), None),
+                (2, (2, 0), Some((0, 1))),
+                (0, (2, 1), None)
+            ],
+            starts
+        );
+    }
+
+    #[test]
+    fn test_stencil() {
+        let cross = Stencil::parse(X_MAS).unwrap();
+        assert_eq!(4, cross.orientations().len());
+        let corner = Stencil::parse("AB\nC.").unwrap();
+        assert_eq!(8, corner.orientations().len());
+        assert_eq!(
+            1,
+            Stencil::parse(".A.\nAAA\n.A.")
+                .unwrap()
+                .orientations()
+                .len()
+        );
+        assert!(Stencil::parse("AB\nC").is_err());
+
+        let data: Vec<String> = "XABX\nXCAX\nBAXC".lines().map(String::from).collect();
+        let grid = WordSearch::parse(&data).unwrap();
+        let found = Stencil::parse("AB").unwrap().find(&grid);
+        let starts: Vec<Vec2u> = found.iter().map(|found| found.start).collect();
+        assert_eq!(vec![(0, 1), (0, 2), (2, 0)], starts);
+        assert_eq!(vec![(0, 2), (1, 2)], found[1].cells);
+        assert_eq!(3, corner.count(&grid));
+    }
+
     #[test]
     #[cfg(feature = "real_inputs")]
     fn test_day_4() {
@@ -1,26 +1,344 @@
+use std::{cmp::Reverse, collections::BinaryHeap, fmt};
+
 use hashbrown::{HashMap, HashSet};
 
 use crate::{parse_pair_from_str, parse_sequence_from_str};
 
-type ParsedInput = (HashMap<u32, Vec<u32>>, Vec<Vec<u32>>);
+use super::{
+    input::{check_lines, sections, Diagnostic, PuzzleInput},
+    report::{Report, Section},
+};
+
+/// The page ordering rules as (before, after) pairs, and the page sequences of the updates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrintQueue {
+    pub rules: Vec<(u32, u32)>,
+    pub updates: Vec<Vec<u32>>,
+}
+
+impl PrintQueue {
+    /// The graph of the ordering rules.
+    pub fn rule_graph(&self) -> RuleGraph {
+        RuleGraph::new(&self.rules)
+    }
+}
+
+/// The ordering rules as a graph, with an edge from each page to the pages that come after it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RuleGraph {
+    successors: HashMap<u32, HashSet<u32>>,
+}
+
+impl RuleGraph {
+    pub fn new(rules: &[(u32, u32)]) -> RuleGraph {
+        let mut successors: HashMap<u32, HashSet<u32>> = HashMap::new();
+        for &(before, after) in rules {
+            successors.entry(before).or_default().insert(after);
+        }
+        RuleGraph { successors }
+    }
+
+    /// Check if a rule requires the first page to come before the second.
+    pub fn has_rule(&self, before: u32, after: u32) -> bool {
+        self.successors
+            .get(&before)
+            .is_some_and(|pages| pages.contains(&after))
+    }
+
+    /// Check if the update follows the rules, in time linear in its pages and their rules.
+    pub fn is_ordered(&self, update: &[u32]) -> bool {
+        let positions = positions(update);
+        update
+            .iter()
+            .enumerate()
+            .all(|(i, page)| self.pages_before(page, i, &positions).next().is_none())
+    }
+
+    /// Find the rules broken by the update, in the order of the pages.
+    pub fn broken_rules(&self, update: &[u32]) -> Vec<BrokenRule> {
+        let positions = positions(update);
+        let mut broken = Vec::new();
+        for (i, &page) in update.iter().enumerate() {
+            let mut before: Vec<(usize, u32)> = self.pages_before(&page, i, &positions).collect();
+            before.sort_unstable();
+            broken.extend(before.into_iter().map(|(pos, other)| BrokenRule {
+                rule: (page, other),
+                positions: (i, pos),
+            }));
+        }
+        broken
+    }
+
+    /// The pages of the update before the index, that the rules require after the page.
+    fn pages_before<'a>(
+        &'a self,
+        page: &u32,
+        index: usize,
+        positions: &'a HashMap<u32, usize>,
+    ) -> impl Iterator<Item = (usize, u32)> + 'a {
+        self.successors
+            .get(page)
+            .into_iter()
+            .flatten()
+            .filter_map(move |other| {
+                positions
+                    .get(other)
+                    .filter(|&&pos| pos < index)
+                    .map(|&pos| (pos, *other))
+            })
+    }
+
+    /// Order the pages of the update with Kahn's algorithm on the rules between them.
+    /// Pages without rules between them keep their order in the update, as the ready page that
+    /// comes first in the update is always taken next.
+    /// Fails with the pages of a cycle if the rules contradict each other, or if a page repeats.
+    pub fn order(&self, update: &[u32]) -> Result<Vec<u32>, String> {
+        let positions = positions(update);
+        if positions.len() < update.len() {
+            let mut seen = HashSet::new();
+            let page = update.iter().find(|&&page| !seen.insert(page)).unwrap();
+            return Err(format!(
+                "Page {} appears more than once in the update.",
+                page
+            ));
+        }
+        let mut in_degrees: HashMap<u32, usize> = update.iter().map(|&page| (page, 0)).collect();
+        for page in update {
+            for other in self.induced_successors(page, &positions) {
+                *in_degrees.get_mut(other).unwrap() += 1;
+            }
+        }
+
+        // The pages without remaining predecessors, keyed by their position in the update
+        let mut ready: BinaryHeap<Reverse<(usize, u32)>> = update
+            .iter()
+            .enumerate()
+            .filter(|(_, page)| in_degrees[*page] == 0)
+            .map(|(i, &page)| Reverse((i, page)))
+            .collect();
+        let mut ordered = Vec::with_capacity(update.len());
+        while let Some(Reverse((_, page))) = ready.pop() {
+            ordered.push(page);
+            for &other in self.induced_successors(&page, &positions) {
+                let in_degree = in_degrees.get_mut(&other).unwrap();
+                *in_degree -= 1;
+                if *in_degree == 0 {
+                    ready.push(Reverse((positions[&other], other)));
+                }
+            }
+        }
+
+        if ordered.len() < update.len() {
+            let remaining: Vec<u32> = update
+                .iter()
+                .copied()
+                .filter(|page| in_degrees[page] > 0)
+                .collect();
+            let cycle: Vec<String> = self
+                .find_cycle(&remaining)
+                .iter()
+                .map(|page| page.to_string())
+                .collect();
+            return Err(format!(
+                "The rules for the update contain a cycle: {}",
+                cycle.join(" -> ")
+            ));
+        }
+
+        Ok(ordered)
+    }
+
+    /// The successors of the page that are in the update.
+    fn induced_successors<'a>(
+        &'a self,
+        page: &u32,
+        positions: &'a HashMap<u32, usize>,
+    ) -> impl Iterator<Item = &'a u32> + 'a {
+        self.successors
+            .get(page)
+            .into_iter()
+            .flatten()
+            .filter(move |other| positions.contains_key(*other))
+    }
+
+    /// Find a cycle among pages that each have a rule from another of the pages, as the pages of
+    /// the cycle with the first page repeated at the end. Walking the rules backwards from any
+    /// page always continues, so it has to return to a page it has already seen.
+    fn find_cycle(&self, pages: &[u32]) -> Vec<u32> {
+        let positions = positions(pages);
+        let mut predecessors: HashMap<u32, u32> = HashMap::new();
+        for page in pages {
+            for &other in self.induced_successors(page, &positions) {
+                predecessors.entry(other).or_insert(*page);
+            }
+        }
+
+        let mut walk = Vec::new();
+        let mut seen = HashMap::new();
+        let mut page = pages[0];
+        loop {
+            if let Some(&start) = seen.get(&page) {
+                // The walk went backwards, so the cycle is reversed. Start it at the first page of
+                // the cycle in the update.
+                let mut cycle = walk.split_off(start);
+                cycle.reverse();
+                let first = (0..cycle.len())
+                    .min_by_key(|&i| positions[&cycle[i]])
+                    .unwrap();
+                cycle.rotate_left(first);
+                cycle.push(cycle[0]);
+                return cycle;
+            }
+            seen.insert(page, walk.len());
+            walk.push(page);
+            page = predecessors[&page];
+        }
+    }
+}
+
+/// A rule (before, after) broken by an update, with the indices of both pages in the update.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BrokenRule {
+    pub rule: (u32, u32),
+    pub positions: (usize, usize),
+}
+
+/// A page moved from an index in an update to its index in the ordered update.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Move {
+    pub page: u32,
+    pub from: usize,
+    pub to: usize,
+}
+
+/// Find the fewest pages to move to turn the update into the ordered update, in the order of the
+/// update. The pages that stay are the longest run of pages already in the right relative order,
+/// found as the longest increasing subsequence of their indices in the ordered update.
+pub fn minimal_moves(update: &[u32], ordered: &[u32]) -> Vec<Move> {
+    let targets = positions(ordered);
+    let indices: Vec<usize> = update.iter().map(|page| targets[page]).collect();
+
+    // The index in the update of the last element of the best subsequence of each length,
+    // and the element before each element in its subsequence
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous: Vec<Option<usize>> = vec![None; update.len()];
+    for (i, &index) in indices.iter().enumerate() {
+        let len = tails.partition_point(|&tail| indices[tail] < index);
+        previous[i] = len.checked_sub(1).map(|prev| tails[prev]);
+        if len == tails.len() {
+            tails.push(i);
+        } else {
+            tails[len] = i;
+        }
+    }
+
+    let mut stays = vec![false; update.len()];
+    let mut next = tails.last().copied();
+    while let Some(i) = next {
+        stays[i] = true;
+        next = previous[i];
+    }
+
+    (0..update.len())
+        .filter(|&i| !stays[i])
+        .map(|i| Move {
+            page: update[i],
+            from: i,
+            to: indices[i],
+        })
+        .collect()
+}
+
+/// Map from each page of the update to its index.
+fn positions(update: &[u32]) -> HashMap<u32, usize> {
+    update
+        .iter()
+        .enumerate()
+        .map(|(i, &page)| (page, i))
+        .collect()
+}
+
+impl PuzzleInput for PrintQueue {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        parse_input(input_data)
+    }
+
+    fn validate(input_data: &[String]) -> Vec<Diagnostic> {
+        let sections = sections(input_data);
+        let mut diagnostics = Vec::new();
+        if let Some(&(start, _)) = sections.get(2) {
+            diagnostics.push(Diagnostic::at(
+                start - 1,
+                "Expected only the rules and the updates, separated by one empty line.".to_owned(),
+            ));
+        }
+
+        let (start, rules) = sections[0];
+        diagnostics.extend(check_lines(rules, start, |line| {
+            parse_pair_from_str::<u32>(line, "|")
+        }));
+
+        let Some(&(start, updates)) = sections.get(1) else {
+            diagnostics.push(Diagnostic::input(
+                "Missing the updates after the rules.".to_owned(),
+            ));
+            return diagnostics;
+        };
+        diagnostics.extend(check_lines(updates, start, |line| {
+            let pages = parse_sequence_from_str::<u32>(line, ",")?;
+            let mut seen = HashSet::new();
+            if let Some(page) = pages.iter().find(|&&page| !seen.insert(page)) {
+                return Err(format!(
+                    "Page {} appears more than once in the update.",
+                    page
+                ));
+            }
+            if pages.len() % 2 == 0 {
+                return Err(format!(
+                    "The update has {} pages, so it has no middle page.",
+                    pages.len()
+                ));
+            }
+            Ok(())
+        }));
+
+        diagnostics
+    }
+}
+
+impl fmt::Display for PrintQueue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (before, after) in &self.rules {
+            writeln!(f, "{}|{}", before, after)?;
+        }
+        writeln!(f)?;
+        for update in &self.updates {
+            let pages: Vec<String> = update.iter().map(|page| page.to_string()).collect();
+            writeln!(f, "{}", pages.join(","))?;
+        }
+        Ok(())
+    }
+}
 
 /// Solve the problem for day five, given the provided data.
 pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
     // Structure input data
-    let (order_rules, sequences) = parse_input(input_data)?;
+    let print_queue = PrintQueue::parse_checked(input_data)?;
+    let rules = print_queue.rule_graph();
+    let sequences = print_queue.updates;
 
     // Solve part 1
     let result_part_1 = sequences
         .iter()
-        .filter(|sequence| validate_sequence(sequence, &order_rules))
+        .filter(|sequence| rules.is_ordered(sequence))
         .map(|sequence| sequence[sequence.len() / 2] as u64)
         .sum();
 
     // Solve part 2
     let result_part_2 = sequences
         .iter()
-        .filter(|sequence| !validate_sequence(sequence, &order_rules))
-        .map(|sequence| fix_sequence_order(sequence, &order_rules))
+        .filter(|sequence| !rules.is_ordered(sequence))
+        .map(|sequence| rules.order(sequence))
         .collect::<Result<Vec<_>, _>>()?
         .into_iter()
         .map(|sequence| sequence[sequence.len() / 2] as u64)
@@ -29,80 +347,86 @@ pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
     Ok(vec![result_part_1, result_part_2])
 }
 
-/// Validate a sequence given the order rules
-fn validate_sequence(sequence: &[u32], order_rules: &HashMap<u32, Vec<u32>>) -> bool {
-    for (index, &page) in sequence.iter().enumerate() {
-        if !sequence[..index]
-            .iter()
-            .all(|other_page| can_come_after(page, *other_page, order_rules))
-        {
-            return false;
-        }
-    }
-    true
-}
+/// Report the rules broken by each update in the wrong order with the positions of their pages,
+/// and the fewest page moves giving the fixed order.
+pub fn explain(input_data: &[String]) -> Result<Report, String> {
+    let print_queue = PrintQueue::parse_checked(input_data)?;
+    let rules = print_queue.rule_graph();
+    let first_update_line = print_queue.rules.len() + 2;
 
-/// Check if the current page can come after the other
-fn can_come_after(
-    current_page: u32,
-    other_page: u32,
-    order_rules: &HashMap<u32, Vec<u32>>,
-) -> bool {
-    order_rules
-        .get(&current_page)
-        .map_or(true, |rules| !rules.contains(&other_page))
-}
-
-/// Correct the sequence order based on the rules
-fn fix_sequence_order(
-    sequence: &[u32],
-    order_rules: &HashMap<u32, Vec<u32>>,
-) -> Result<Vec<u32>, String> {
-    // Find valid locations for each page
-    let mut new_sequence = Vec::with_capacity(sequence.len());
-    let mut remaining_pages = HashSet::from_iter(sequence.iter().copied());
-    while !remaining_pages.is_empty() {
-        let next = find_next_valid(&remaining_pages, order_rules)?;
-        new_sequence.push(next);
-        remaining_pages.remove(&next);
-    }
-
-    Ok(new_sequence)
-}
-
-/// Return the next valid page from the available options
-fn find_next_valid(
-    remaining_pages: &HashSet<u32>,
-    order_rules: &HashMap<u32, Vec<u32>>,
-) -> Result<u32, String> {
-    for &page in remaining_pages.iter() {
-        if remaining_pages
+    let mut broken_updates = Section::new(
+        "Updates in the wrong order",
+        &[
+            "Line",
+            "Update",
+            "Broken rules",
+            "Fixed update",
+            "Moves",
+            "Fixed middle page",
+        ],
+    );
+    let mut broken_rules = Section::new(
+        "Broken rules",
+        &["Line", "Rule", "Before position", "After position"],
+    );
+    let mut moves = Section::new("Moves", &["Line", "Page", "From position", "To position"]);
+    for (i, sequence) in print_queue.updates.iter().enumerate() {
+        let broken = rules.broken_rules(sequence);
+        if broken.is_empty() {
+            continue;
+        }
+        let line = first_update_line + i;
+        let fixed = rules.order(sequence)?;
+        let fixed_moves = minimal_moves(sequence, &fixed);
+        let join = |pages: &[u32]| {
+            let pages: Vec<String> = pages.iter().map(|page| page.to_string()).collect();
+            pages.join(",")
+        };
+        let rule_texts: Vec<String> = broken
             .iter()
-            .all(|&other_page| other_page == page || can_come_after(other_page, page, order_rules))
-        {
-            // Adding this page will not invalidate any remaining page
-            return Ok(page);
+            .map(|broken| format!("{}|{}", broken.rule.0, broken.rule.1))
+            .collect();
+        broken_updates.push_row(vec![
+            line.into(),
+            join(sequence).into(),
+            rule_texts.join(" ").into(),
+            join(&fixed).into(),
+            fixed_moves.len().into(),
+            (fixed[fixed.len() / 2] as u64).into(),
+        ]);
+
+        for (broken, text) in broken.iter().zip(rule_texts) {
+            broken_rules.push_row(vec![
+                line.into(),
+                text.into(),
+                (broken.positions.0 + 1).into(),
+                (broken.positions.1 + 1).into(),
+            ]);
+        }
+        for fixed_move in fixed_moves {
+            moves.push_row(vec![
+                line.into(),
+                fixed_move.page.into(),
+                (fixed_move.from + 1).into(),
+                (fixed_move.to + 1).into(),
+            ]);
         }
     }
 
-    Err("No valid order found".to_owned())
+    Ok(Report::new(vec![broken_updates, broken_rules, moves]))
 }
 
 /// Parse the input into separate data structures for the order pairs and the sequences
-fn parse_input(input_data: &[String]) -> Result<ParsedInput, String> {
+fn parse_input(input_data: &[String]) -> Result<PrintQueue, String> {
     let mut iter = input_data.split(|line| line.is_empty());
 
-    // Parse the order pairs into a map
-    let order_rules = iter
+    // Parse the order pairs
+    let rules = iter
         .next()
         .unwrap_or(&[])
         .iter()
         .map(|line| parse_pair_from_str(line, "|"))
-        .try_fold(HashMap::new(), |mut acc: HashMap<u32, Vec<u32>>, result| {
-            let (key, value) = result?;
-            acc.entry(key).or_default().push(value);
-            Ok::<_, String>(acc)
-        })?;
+        .collect::<Result<Vec<_>, _>>()?;
 
     // Parse the page sequences into vecs
     let sequences = iter
@@ -112,7 +436,10 @@ fn parse_input(input_data: &[String]) -> Result<ParsedInput, String> {
         .map(|line| parse_sequence_from_str(line, ","))
         .collect::<Result<Vec<_>, _>>()?;
 
-    Ok((order_rules, sequences))
+    Ok(PrintQueue {
+        rules,
+        updates: sequences,
+    })
 }
 
 #[cfg(test)]
@@ -120,6 +447,124 @@ mod tests {
     use std::str::FromStr;
 
     use super::*;
+    use crate::problems::{
+        input::testing::{assert_text_round_trip, check_round_trips},
+        report::Value,
+    };
+
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<PrintQueue>("47|53\n97|13\n\n75,47,61,53,29\n97,61,53,29,13");
+        check_round_trips(5, |rng| PrintQueue {
+            rules: (0..rng.range(0, 10))
+                .map(|_| (rng.range(10, 100) as u32, rng.range(10, 100) as u32))
+                .collect(),
+            updates: (0..rng.range(0, 5))
+                .map(|_| {
+                    (0..rng.range(1, 8))
+                        .map(|_| rng.range(10, 100) as u32)
+                        .collect()
+                })
+                .collect(),
+        });
+    }
+
+    #[test]
+    fn test_validate() {
+        let data: Vec<String> = "47|53\n97-13\n\n75,47,61,47,29\n75,29\n97,61,53"
+            .lines()
+            .map(String::from)
+            .collect();
+        let lines: Vec<Option<usize>> = PrintQueue::validate(&data)
+            .iter()
+            .map(|diagnostic| diagnostic.line)
+            .collect();
+        assert_eq!(vec![Some(2), Some(4), Some(5)], lines);
+        assert!(solve(&data).is_err());
+    }
+
+    #[test]
+    fn test_rule_graph() {
+        let rules = RuleGraph::new(&[(1, 2), (2, 3), (1, 3), (4, 5)]);
+        assert!(rules.is_ordered(&[1, 4, 2, 5, 3]));
+        assert!(!rules.is_ordered(&[3, 1, 2]));
+        let broken: Vec<((u32, u32), (usize, usize))> = rules
+            .broken_rules(&[3, 2, 1])
+            .iter()
+            .map(|broken| (broken.rule, broken.positions))
+            .collect();
+        assert_eq!(
+            vec![((2, 3), (1, 0)), ((1, 3), (2, 0)), ((1, 2), (2, 1))],
+            broken
+        );
+        assert_eq!(Ok(vec![5, 1, 2, 3]), rules.order(&[5, 3, 2, 1]));
+        assert_eq!(Ok(vec![4, 5, 1]), rules.order(&[5, 4, 1]));
+        assert_eq!(
+            Err("Page 2 appears more than once in the update.".to_owned()),
+            rules.order(&[1, 2, 2])
+        );
+
+        let rules = RuleGraph::new(&[(1, 2), (2, 3), (3, 4), (4, 2), (5, 1)]);
+        assert_eq!(
+            Err("The rules for the update contain a cycle: 2 -> 3 -> 4 -> 2".to_owned()),
+            rules.order(&[1, 2, 3, 4, 5])
+        );
+    }
+
+    #[test]
+    fn test_minimal_moves() {
+        assert!(minimal_moves(&[1, 2, 3], &[1, 2, 3]).is_empty());
+        assert_eq!(
+            vec![Move {
+                page: 5,
+                from: 0,
+                to: 4
+            }],
+            minimal_moves(&[5, 1, 2, 3, 4], &[1, 2, 3, 4, 5])
+        );
+
+        let moves = minimal_moves(&[4, 1, 5, 2, 6, 3], &[1, 2, 3, 4, 5, 6]);
+        let pages: Vec<u32> = moves.iter().map(|m| m.page).collect();
+        assert_eq!(vec![4, 5, 6], pages);
+        assert_eq!(
+            3,
+            minimal_moves(&[6, 5, 4, 3], &[3, 4, 5, 6]).len(),
+            "A reversed update keeps only one page in place"
+        );
+    }
+
+    #[test]
+    fn test_explain() {
+        let data: Vec<String> = "1|2\n2|3\n1|3\n\n3,2,1\n1,2,3"
+            .lines()
+            .map(String::from)
+            .collect();
+        let report = explain(&data).unwrap();
+        let [updates, broken_rules, moves] = &report.sections[..] else {
+            panic!(
+                "Expected three sections but found {}",
+                report.sections.len()
+            );
+        };
+
+        assert_eq!(
+            vec![
+                Value::from(5),
+                "3,2,1".into(),
+                "2|3 1|3 1|2".into(),
+                "1,2,3".into(),
+                2.into(),
+                2.into()
+            ],
+            updates.rows[0]
+        );
+        assert_eq!(1, updates.rows.len());
+        assert_eq!(
+            vec![Value::from(5), "1|3".into(), 3.into(), 1.into()],
+            broken_rules.rows[1]
+        );
+        assert_eq!(2, moves.rows.len());
+    }
 
     #[test]
     fn test_day_5_example() {
@@ -1,98 +1,233 @@
+use std::fmt;
+
 use hashbrown::{HashMap, HashSet};
 
-use crate::{increment_2d_index, ortho_dir, Vec2i, Vec2u};
+use crate::{increment_2d_index, ortho_dir, BitGrid, PositionSet, Vec2i, Vec2u};
+
+use super::{
+    input::{check_grid, check_unique, grid_cells, grid_size, write_grid, Diagnostic, PuzzleInput},
+    render::{Canvas, Highlight},
+    simulation::{detect_cycle_brent, Outcome, Simulation, SimulationRunner},
+    utils, Variant,
+};
+
+/// The available implementations for day 6.
+pub const VARIANTS: &[Variant] = &[
+    Variant {
+        name: "hashed",
+        solve,
+    },
+    Variant {
+        name: "bitgrid",
+        solve: solve_bitgrid,
+    },
+];
+
+/// The characters for the guard facing up, right, down and left.
+const GUARD_CHARS: [(char, Vec2i); 4] =
+    [('^', (-1, 0)), ('>', (0, 1)), ('v', (1, 0)), ('<', (0, -1))];
+
+/// The map of the lab, with the obstacles and the starting position and direction of the guard.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LabMap {
+    pub size: Vec2u,
+    pub obstacles: HashSet<Vec2u>,
+    pub guard: (Vec2u, Vec2i),
+}
+
+impl PuzzleInput for LabMap {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        let size = grid_size(input_data)?;
+        let mut obstacles = HashSet::new();
+        let mut guard = None;
+        for (pos, c) in grid_cells(input_data) {
+            if c == '#' {
+                obstacles.insert(pos);
+            } else if let Some(&(_, dir)) =
+                GUARD_CHARS.iter().find(|(guard_char, _)| *guard_char == c)
+            {
+                guard.get_or_insert((pos, dir));
+            }
+        }
+
+        Ok(LabMap {
+            size,
+            obstacles,
+            guard: guard.ok_or("Not valid guard found.")?,
+        })
+    }
+
+    fn validate(input_data: &[String]) -> Vec<Diagnostic> {
+        let guard_chars: Vec<char> = GUARD_CHARS.iter().map(|&(c, _)| c).collect();
+        let mut diagnostics = check_grid(input_data, 0, |c| {
+            c == '.' || c == '#' || guard_chars.contains(&c)
+        });
+        diagnostics.extend(check_unique(input_data, 0, &guard_chars, "guard"));
+        diagnostics
+    }
+}
 
-use super::utils;
+impl fmt::Display for LabMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (guard_pos, guard_dir) = self.guard;
+        let guard_char = GUARD_CHARS
+            .iter()
+            .find(|(_, dir)| *dir == guard_dir)
+            .map_or('?', |&(c, _)| c);
+        write_grid(f, self.size, |pos| {
+            if pos == guard_pos {
+                guard_char
+            } else if self.obstacles.contains(&pos) {
+                '#'
+            } else {
+                '.'
+            }
+        })
+    }
+}
 
 /// Solve the problem for day six, given the provided data.
 pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
-    // Find all obstacles
-    let mut obstacles: HashSet<Vec2u> = input_data
-        .iter()
-        .enumerate()
-        .flat_map(|(i, row)| {
-            row.chars()
-                .enumerate()
-                .filter(|&(_, char)| char == '#')
-                .map(move |(j, _)| (i, j))
-        })
-        .collect();
+    let map = LabMap::parse_checked(input_data)?;
+    solve_with_obstacles(map.guard, map.obstacles, map.size)
+}
 
-    let original_state = find_guard_pos_and_dir(input_data)?;
-    let limits = (input_data.len(), input_data[0].len());
+/// Solve the problem for day six, with the obstacles stored in a bit grid.
+pub fn solve_bitgrid(input_data: &[String]) -> Result<Vec<u64>, String> {
+    let map = LabMap::parse_checked(input_data)?;
+    let obstacles = BitGrid::from_positions(map.size, map.obstacles);
+    solve_with_obstacles(map.guard, obstacles, map.size)
+}
 
+/// Solve both parts with any representation of the obstacles.
+fn solve_with_obstacles<O: PositionSet>(
+    original_state: (Vec2u, Vec2i),
+    mut obstacles: O,
+    limits: Vec2u,
+) -> Result<Vec<u64>, String> {
     // Part 1
-    let all_states =
+    let first_dirs =
         solve_part_1(original_state, &obstacles, limits).expect("Part one should be solveable.");
 
     // Part 2
-    let result_part_2 = solve_part_2(&all_states, &mut obstacles, limits);
+    let result_part_2 = solve_part_2(&first_dirs, &mut obstacles, limits)?;
 
-    Ok(vec![all_states.keys().len() as u64, result_part_2])
+    Ok(vec![first_dirs.len() as u64, result_part_2])
 }
 
-/// Solve part 1
-fn solve_part_1(
-    original_state: (Vec2u, Vec2i),
-    obstacles: &HashSet<Vec2u>,
+/// Render the map with the cells visited by the guard, and the guard's start position.
+pub fn render(input_data: &[String]) -> Result<Canvas, String> {
+    let LabMap {
+        size,
+        obstacles,
+        guard: original_state,
+    } = LabMap::parse_checked(input_data)?;
+    let first_dirs =
+        solve_part_1(original_state, &obstacles, size).ok_or("The guard is stuck in a loop.")?;
+
+    let mut canvas = Canvas::from_lines(input_data);
+    canvas.overlay(first_dirs.keys().copied(), Highlight::Visited);
+    canvas.highlight(original_state.0, Highlight::Marker);
+
+    Ok(canvas)
+}
+
+/// The guard walking through the map, until leaving it.
+struct GuardWalk<'a, O> {
+    state: Option<(Vec2u, Vec2i)>,
+    obstacles: &'a O,
     limits: Vec2u,
-) -> Option<HashMap<Vec2u, Vec<Vec2i>>> {
-    let mut current_state = original_state;
-    let mut previous_states: HashMap<Vec2u, Vec<Vec2i>> = HashMap::new();
-    previous_states.insert(original_state.0, vec![original_state.1]);
-    while let Some(next_state) = update_state(current_state, obstacles, limits) {
-        if !add_state(&next_state, &mut previous_states) {
-            return None;
+}
+
+// Implemented by hand, since the obstacles are shared instead of cloned.
+impl<O> Clone for GuardWalk<'_, O> {
+    fn clone(&self) -> Self {
+        GuardWalk {
+            state: self.state,
+            obstacles: self.obstacles,
+            limits: self.limits,
         }
-        current_state = next_state;
     }
+}
 
-    // Find all unique positions
-    Some(previous_states)
+impl<O: PositionSet> Simulation for GuardWalk<'_, O> {
+    type Snapshot = Option<(Vec2u, Vec2i)>;
+
+    fn step(&mut self) -> Result<(), String> {
+        self.state = self
+            .state
+            .and_then(|state| update_state(state, self.obstacles, self.limits));
+        Ok(())
+    }
+
+    fn snapshot(&self) -> Self::Snapshot {
+        self.state
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.state.is_none()
+    }
 }
 
-/// Add the state to the list if not a duplicate
-fn add_state(state: &(Vec2u, Vec2i), previous_states: &mut HashMap<Vec2u, Vec<Vec2i>>) -> bool {
-    let dirs = previous_states
-        .entry(state.0)
-        .or_insert(Vec::with_capacity(8));
+/// Solve part 1, finding the first direction the guard had on each visited position.
+/// Returns [`None`] if the guard is stuck in a loop.
+fn solve_part_1<O: PositionSet>(
+    original_state: (Vec2u, Vec2i),
+    obstacles: &O,
+    limits: Vec2u,
+) -> Option<HashMap<Vec2u, Vec2i>> {
+    let walk = GuardWalk {
+        state: Some(original_state),
+        obstacles,
+        limits,
+    };
+
+    // The guard can only be in this many different states before repeating one
+    let max_steps = 4 * limits.0 * limits.1;
+    let mut runner = SimulationRunner::new(walk)
+        .with_history()
+        .with_step_limit(max_steps);
+    if let Outcome::StepLimit(_) = runner.run().ok()? {
+        return None;
+    }
 
-    if !dirs.contains(&state.1) {
-        dirs.push(state.1);
-        true
-    } else {
-        false
+    let mut first_dirs = HashMap::new();
+    for &(pos, dir) in runner.history().iter().flatten() {
+        first_dirs.entry(pos).or_insert(dir);
     }
+
+    Some(first_dirs)
 }
 
 /// Solve part 2
-fn solve_part_2(
-    all_states: &HashMap<Vec2u, Vec<Vec2i>>,
-    obstacles: &mut HashSet<Vec2u>,
+fn solve_part_2<O: PositionSet>(
+    first_dirs: &HashMap<Vec2u, Vec2i>,
+    obstacles: &mut O,
     limits: Vec2u,
-) -> u64 {
-    // Put obstacle on all visited pos, check if path is broken
+) -> Result<u64, String> {
+    // Put obstacle on all visited pos, check if the guard ends up in a loop
     let mut count = 0;
-    for (&(i, j), dirs) in all_states.iter() {
-        obstacles.insert((i, j));
-        let (v_i, v_j) = dirs[0];
+    for (&(i, j), &(v_i, v_j)) in first_dirs.iter() {
         let prev_pos = increment_2d_index(i, j, -v_i, -v_j, 1).unwrap();
-        match solve_part_1((prev_pos, (v_i, v_j)), obstacles, limits) {
-            Some(_) => (),
-            None => {
-                count += 1;
-            }
+        obstacles.insert((i, j));
+        let walk = GuardWalk {
+            state: Some((prev_pos, (v_i, v_j))),
+            obstacles,
+            limits,
         };
+        if detect_cycle_brent(&walk, None)?.is_some() {
+            count += 1;
+        }
         obstacles.remove(&(i, j));
     }
 
-    count
+    Ok(count)
 }
 
 /// Update the position and direction based on the guards movement.
-fn update_state(
+fn update_state<O: PositionSet>(
     state: (Vec2u, Vec2i),
-    obstacles: &HashSet<Vec2u>,
+    obstacles: &O,
     limits: Vec2u,
 ) -> Option<(Vec2u, Vec2i)> {
     let ((i, j), (v_i, v_j)) = state;
@@ -109,27 +244,54 @@ fn update_state(
     None
 }
 
-/// Find the location and direction of travel for the guard in the data.
-fn find_guard_pos_and_dir(input_data: &[String]) -> Result<(Vec2u, Vec2i), String> {
-    for (i, s) in input_data.iter().enumerate() {
-        if let Some(j) = s.find(">") {
-            return Ok(((i, j), (0, 1)));
-        } else if let Some(j) = s.find("^") {
-            return Ok(((i, j), (-1, 0)));
-        } else if let Some(j) = s.find("<") {
-            return Ok(((i, j), (0, -1)));
-        } else if let Some(j) = s.find("v") {
-            return Ok(((i, j), (-1, 0)));
-        }
-    }
-    Err("Not valid guard found.".to_owned())
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::problems::input::testing::{assert_text_round_trip, check_round_trips};
     use std::str::FromStr;
 
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<LabMap>("....#.....\n.#........\n#......v..\n......#...");
+        check_round_trips(6, |rng| {
+            let size = (rng.range(1, 12), rng.range(1, 12));
+            let guard_pos = (rng.range(0, size.0), rng.range(0, size.1));
+            let obstacles = (0..size.0)
+                .flat_map(|i| (0..size.1).map(move |j| (i, j)))
+                .filter(|&pos| pos != guard_pos && rng.chance(0.2))
+                .collect();
+            LabMap {
+                size,
+                obstacles,
+                guard: (guard_pos, rng.choose(&GUARD_CHARS).1),
+            }
+        });
+    }
+
+    #[test]
+    fn test_guard_facing_down() {
+        // A guard drawn as 'v' walks down, it used to be read as facing up
+        let data: Vec<String> = "...\n.v.\n...\n...".lines().map(String::from).collect();
+        assert_eq!(((1, 1), (1, 0)), LabMap::parse(&data).unwrap().guard);
+        assert_eq!(3, solve(&data).unwrap()[0]);
+    }
+
+    #[test]
+    fn test_validate() {
+        let data: Vec<String> = "....#\n.^...\n...>.\n....."
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            vec![Diagnostic::at(
+                2,
+                "Found another guard at column 4, the first is on line 2.".to_owned()
+            )],
+            LabMap::validate(&data)
+        );
+        assert!(solve(&data).is_err());
+    }
+
     #[test]
     fn test_example_data() {
         let mut data = Vec::new();
@@ -157,6 +319,7 @@ mod tests {
             "Result for part 2 example should be 6 but was {}",
             result[1]
         );
+        assert_eq!(result, solve_bitgrid(&data).unwrap());
     }
 
     #[test]
@@ -1,8 +1,42 @@
-use super::utils;
+use std::fmt;
+
+use super::{
+    input::{check_lines, Diagnostic, PuzzleInput},
+    math::concat_digits,
+    utils,
+};
+
+/// The calibration equations, as the test value and the numbers to combine.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Equations {
+    pub equations: Vec<(u64, Vec<u64>)>,
+}
+
+impl PuzzleInput for Equations {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        Ok(Equations {
+            equations: parse_inputs(input_data)?,
+        })
+    }
+
+    fn validate(input_data: &[String]) -> Vec<Diagnostic> {
+        check_lines(input_data, 0, parse_row)
+    }
+}
+
+impl fmt::Display for Equations {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (result, inputs) in &self.equations {
+            let inputs: Vec<String> = inputs.iter().map(|val| val.to_string()).collect();
+            writeln!(f, "{}: {}", result, inputs.join(" "))?;
+        }
+        Ok(())
+    }
+}
 
 /// Solve the problem for day seven, given the provided data.
 pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
-    let equations = parse_inputs(input_data)?;
+    let equations = Equations::parse_checked(input_data)?.equations;
 
     let result_part_1 = equations
         .iter()
@@ -59,19 +93,12 @@ fn execute_operation(lhs: &u64, rhs: &u64, operation: &char) -> Result<u64, Stri
     match operation {
         'x' => Ok(lhs * rhs),
         '+' => Ok(lhs + rhs),
-        '|' => concat_values(lhs, rhs),
+        '|' => concat_digits(*lhs, *rhs)
+            .ok_or_else(|| format!("Failed to concatenate {} and {}", lhs, rhs)),
         _ => Err("Invalid operation".to_owned()),
     }
 }
 
-/// Concatenate two values into a new value
-fn concat_values(lhs: &u64, rhs: &u64) -> Result<u64, String> {
-    let mut s = lhs.to_string();
-    s.push_str(&rhs.to_string());
-    s.parse::<u64>()
-        .map_err(|_| format!("Failed to parse {} into u64", s))
-}
-
 /// Parse the input data into the result and the inputs
 fn parse_inputs(input_data: &[String]) -> Result<Vec<(u64, Vec<u64>)>, String> {
     input_data.iter().map(|s| parse_row(s)).collect()
@@ -89,7 +116,10 @@ fn parse_row(row: &str) -> Result<(u64, Vec<u64>), String> {
         .parse::<u64>()
         .map_err(|_| format!("Cannot parse u64 from {}", parts[0]))?;
 
-    let inputs = utils::parse_sequence_from_str::<u64>(&parts[1][1..], " ")?;
+    let inputs = parts[1]
+        .strip_prefix(' ')
+        .ok_or(format!("Expected a space after the colon in {}.", row))?;
+    let inputs = utils::parse_sequence_from_str::<u64>(inputs, " ")?;
 
     Ok((result, inputs))
 }
@@ -97,8 +127,42 @@ fn parse_row(row: &str) -> Result<(u64, Vec<u64>), String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::problems::input::testing::{assert_text_round_trip, check_round_trips};
     use std::str::FromStr;
 
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<Equations>("190: 10 19\n3267: 81 40 27\n83: 17 5");
+        check_round_trips(7, |rng| Equations {
+            equations: (0..rng.range(0, 10))
+                .map(|_| {
+                    let inputs = (0..rng.range(1, 8))
+                        .map(|_| rng.range(1, 1000) as u64)
+                        .collect();
+                    (rng.next_u64() % 1_000_000_000, inputs)
+                })
+                .collect(),
+        });
+    }
+
+    #[test]
+    fn test_validate() {
+        let data: Vec<String> = "190: 10 19\n3267 81 40 27\n83: 17 x"
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            vec![
+                Diagnostic::at(
+                    1,
+                    "Failed to split 3267 81 40 27 into result and inputs.".to_owned()
+                ),
+                Diagnostic::at(2, "Failed to parse u64 from x".to_owned()),
+            ],
+            Equations::validate(&data)
+        );
+    }
+
     #[test]
     fn test_example_data() {
         let mut data = Vec::new();
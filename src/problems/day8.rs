@@ -1,42 +1,67 @@
+use std::fmt;
+
 use hashbrown::{HashMap, HashSet};
 
 use crate::Vec2u;
 
-use super::utils;
+use super::{
+    geometry::{line_through, ray},
+    input::{check_grid, grid_cells, grid_size, write_grid, Diagnostic, PuzzleInput},
+    utils,
+};
 
 const NON_ANTENNA_SYMBOLS: &[char] = &['.', '#'];
 
-/// Solve the problem for day eight, given the provided data.
-pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
-    let antenna_locations = parse_antenna_locations(input_data);
+/// The map of the antennas, with the locations of each frequency in row-major order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AntennaMap {
+    pub size: Vec2u,
+    pub antennas: HashMap<char, Vec<Vec2u>>,
+}
 
-    let unique_part_1 = find_unique_antinodes(
-        &antenna_locations,
-        (input_data.len(), input_data[0].len()),
-        true,
-    );
+impl PuzzleInput for AntennaMap {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        let size = grid_size(input_data)?;
+        let mut antennas = HashMap::new();
+        for (pos, c) in grid_cells(input_data) {
+            if !c.is_ascii_alphanumeric() && !NON_ANTENNA_SYMBOLS.contains(&c) {
+                return Err(format!("Invalid character {} at {:?}.", c, pos));
+            } else if !NON_ANTENNA_SYMBOLS.contains(&c) {
+                antennas.entry(c).or_insert_with(Vec::new).push(pos);
+            }
+        }
 
-    let unique_part_2 = find_unique_antinodes(
-        &antenna_locations,
-        (input_data.len(), input_data[0].len()),
-        false,
-    );
+        Ok(AntennaMap { size, antennas })
+    }
 
-    Ok(vec![unique_part_1.len() as u64, unique_part_2.len() as u64])
+    fn validate(input_data: &[String]) -> Vec<Diagnostic> {
+        check_grid(input_data, 0, |c| {
+            c.is_ascii_alphanumeric() || NON_ANTENNA_SYMBOLS.contains(&c)
+        })
+    }
 }
 
-/// Parse all the locations for each type of antenna
-fn parse_antenna_locations(input_data: &[String]) -> HashMap<char, Vec<Vec2u>> {
-    let mut locations = HashMap::new();
-    for (i, s) in input_data.iter().enumerate() {
-        for (j, c) in s.chars().enumerate() {
-            if !NON_ANTENNA_SYMBOLS.contains(&c) {
-                locations.entry(c).or_insert_with(Vec::new).push((i, j));
-            }
-        }
+impl fmt::Display for AntennaMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let frequencies: HashMap<Vec2u, char> = self
+            .antennas
+            .iter()
+            .flat_map(|(&c, locations)| locations.iter().map(move |&pos| (pos, c)))
+            .collect();
+        write_grid(f, self.size, |pos| {
+            frequencies.get(&pos).copied().unwrap_or('.')
+        })
     }
+}
+
+/// Solve the problem for day eight, given the provided data.
+pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
+    let map = AntennaMap::parse_checked(input_data)?;
+
+    let unique_part_1 = find_unique_antinodes(&map.antennas, map.size, true);
+    let unique_part_2 = find_unique_antinodes(&map.antennas, map.size, false);
 
-    locations
+    Ok(vec![unique_part_1.len() as u64, unique_part_2.len() as u64])
 }
 
 /// Find and count the unique anti nodes.
@@ -65,11 +90,6 @@ fn find_unique_antinodes(
     unique_pos
 }
 
-/// Check if antinode position is in bounds
-fn in_bounds(node: Vec2u, bounds: &Vec2u) -> bool {
-    node.0 < bounds.0 && node.1 < bounds.1
-}
-
 // Compute all the antinodes
 fn compute_all_antinodes(
     first_antenna: Vec2u,
@@ -77,59 +97,60 @@ fn compute_all_antinodes(
     bounds: Vec2u,
     single_pass: bool,
 ) -> Vec<Vec2u> {
-    let mut all_nodes = Vec::new();
-    let mut factor = 1;
-    loop {
-        let new_nodes = compute_antinodes(first_antenna, second_antenna, factor);
-        if !add_nodes(new_nodes, &mut all_nodes, &bounds) || single_pass {
-            break;
-        }
-        factor += 1;
-    }
-
-    all_nodes
-}
-
-// Add the valid nodes to the list. Return false if no nodes added.
-fn add_nodes(new_nodes: [Option<Vec2u>; 2], all_nodes: &mut Vec<Vec2u>, bounds: &Vec2u) -> bool {
-    let mut node_added = false;
-    for &node in &new_nodes {
-        if let Some(valid_node) = node {
-            if in_bounds(valid_node, bounds) {
-                all_nodes.push(valid_node);
-                node_added = true;
-            }
-        }
-    }
-    node_added
-}
-
-// Compute the antinodes for two antenna locations
-fn compute_antinodes(
-    first_antenna: Vec2u,
-    second_antenna: Vec2u,
-    factor: usize,
-) -> [Option<Vec2u>; 2] {
     if first_antenna == second_antenna {
-        return [None, None];
+        return Vec::new();
     }
 
-    let (diff_i, diff_j) = utils::difference_2i(first_antenna, second_antenna)
-        .expect("Difference between nodes should be valid.");
-
-    let first_node =
-        utils::increment_2d_index(first_antenna.0, first_antenna.1, -diff_i, -diff_j, factor);
-    let second_node =
-        utils::increment_2d_index(second_antenna.0, second_antenna.1, diff_i, diff_j, factor);
-
-    [first_node, second_node]
+    if single_pass {
+        let (diff_i, diff_j) = utils::difference_2i(first_antenna, second_antenna)
+            .expect("Difference between nodes should be valid.");
+        ray(first_antenna, (-diff_i, -diff_j), bounds)
+            .take(1)
+            .chain(ray(second_antenna, (diff_i, diff_j), bounds).take(1))
+            .collect()
+    } else {
+        line_through(first_antenna, second_antenna, bounds).collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::problems::input::testing::{assert_text_round_trip, check_round_trips};
     use std::str::FromStr;
 
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<AntennaMap>(
+            "............\n........0...\n.....0......\n......A.....",
+        );
+        check_round_trips(8, |rng| {
+            let size = (rng.range(1, 12), rng.range(1, 12));
+            let mut antennas = HashMap::new();
+            for i in 0..size.0 {
+                for j in 0..size.1 {
+                    if rng.chance(0.1) {
+                        let c = rng.choose(&['0', 'a', 'A', 'z', '9']);
+                        antennas.entry(c).or_insert_with(Vec::new).push((i, j));
+                    }
+                }
+            }
+            AntennaMap { size, antennas }
+        });
+    }
+
+    #[test]
+    fn test_validate() {
+        let data: Vec<String> = "..a.\n.#0.\n...!".lines().map(String::from).collect();
+        assert_eq!(
+            vec![Diagnostic::at(
+                2,
+                "Invalid character '!' at column 4.".to_owned()
+            ),],
+            AntennaMap::validate(&data)
+        );
+    }
+
     #[test]
     fn test_example_data() {
         let mut data = Vec::new();
@@ -1,9 +1,56 @@
+use std::fmt;
+
 use hashbrown::HashSet;
 
+use crate::IntervalSet;
+
+use super::input::{check_lines, Diagnostic, PuzzleInput};
+
+/// The dense disk map, alternating between the lengths of files and free space.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiskMap {
+    pub lengths: Vec<u8>,
+}
+
+impl PuzzleInput for DiskMap {
+    fn parse(input_data: &[String]) -> Result<Self, String> {
+        let lengths = input_data
+            .join("")
+            .chars()
+            .map(|c| {
+                c.to_digit(10)
+                    .map(|val| val as u8)
+                    .ok_or(format!("Invalid digit {} in the disk map.", c))
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(DiskMap { lengths })
+    }
+
+    fn validate(input_data: &[String]) -> Vec<Diagnostic> {
+        if input_data.iter().all(|line| line.is_empty()) {
+            return vec![Diagnostic::input("The disk map is empty.".to_owned())];
+        }
+        check_lines(input_data, 0, |line| {
+            match line.chars().enumerate().find(|(_, c)| !c.is_ascii_digit()) {
+                Some((j, c)) => Err(format!("Invalid digit '{}' at column {}.", c, j + 1)),
+                None => Ok(()),
+            }
+        })
+    }
+}
+
+impl fmt::Display for DiskMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits: String = self.lengths.iter().map(|val| val.to_string()).collect();
+        writeln!(f, "{}", digits)
+    }
+}
+
 /// Solve the problem for day nine, given the provided data.
 pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
-    let data = input_data.join("");
-    let expanded_mem = expand_mem(&data);
+    let disk_map = DiskMap::parse_checked(input_data)?;
+    let expanded_mem = expand_mem(&disk_map.lengths);
     // Part 1
     let mut part_1_data = expanded_mem.clone();
     compact_mem(&mut part_1_data);
@@ -20,16 +67,10 @@ pub fn solve(input_data: &[String]) -> Result<Vec<u64>, String> {
 }
 
 /// Expand the compact data into a full list of the memory
-fn expand_mem(data: &str) -> Vec<Option<u64>> {
-    let input_values: Vec<u64> = data
-        .chars()
-        .filter_map(|c| c.to_digit(10))
-        .map(|v| v as u64)
-        .collect();
-
-    let mut expanded = Vec::with_capacity(input_values.len() * 5);
+fn expand_mem(lengths: &[u8]) -> Vec<Option<u64>> {
+    let mut expanded = Vec::with_capacity(lengths.len() * 5);
     let mut is_space = false;
-    for (id, &val) in input_values.iter().enumerate() {
+    for (id, &val) in lengths.iter().enumerate() {
         for _ in 0..val {
             if is_space {
                 expanded.push(None);
@@ -71,56 +112,40 @@ fn compact_mem(expanded_mem: &mut [Option<u64>]) {
     }
 }
 
-/// Compact the memory as blocks.
+/// Compact the memory as blocks, keeping track of the free space in an interval set.
 fn compact_mem_blocks(expanded_mem: &mut [Option<u64>]) {
+    let mut free_space = IntervalSet::new();
+    for (i, _) in expanded_mem
+        .iter()
+        .enumerate()
+        .filter(|(_, opt)| opt.is_none())
+    {
+        free_space.insert(i..i + 1);
+    }
+
     let mut prev_data_index = expanded_mem.len();
     let mut processed_ids = HashSet::new();
     while let Some((data_index, data_len, val)) =
         find_next_data_block(prev_data_index, expanded_mem)
     {
-        let mut prev_free_index = 0;
-        while let Some((free_index, free_len)) = find_next_free_block(prev_free_index, expanded_mem)
-        {
-            if !processed_ids.contains(&val) && free_index < data_index && free_len >= data_len {
+        if processed_ids.insert(val) {
+            if let Some(free_index) = free_space
+                .first_fit(data_len)
+                .filter(|&free_index| free_index < data_index)
+            {
                 for i in 0..data_len {
                     expanded_mem[data_index + i] = None;
                     expanded_mem[free_index + i] = Some(val);
                 }
-                processed_ids.insert(val);
-                break;
-            } else {
-                // Update new location for triggering next search
-                prev_free_index = free_index + free_len;
+                free_space.remove(free_index..free_index + data_len);
+                free_space.insert(data_index..data_index + data_len);
             }
         }
         prev_data_index = data_index;
     }
 }
 
-/// Find the next free block of memory. Returns a tuple with the start index and the length.
-fn find_next_free_block(
-    start_index: usize,
-    expanded_mem: &[Option<u64>],
-) -> Option<(usize, usize)> {
-    if let Some(free_index) = expanded_mem[start_index..]
-        .iter()
-        .enumerate()
-        .find(|(_, opt)| opt.is_none())
-        .map(|(id, _)| id)
-    {
-        let index = start_index + free_index;
-        let mut len = 1;
-        while index + len < expanded_mem.len() - 1 && expanded_mem[index + len].is_none() {
-            len += 1
-        }
-
-        Some((index, len))
-    } else {
-        None
-    }
-}
-
-/// Find the next free block of memory. Returns a tuple with the start index and the length, and the value.
+/// Find the next block of data before the index. Returns a tuple with the start index, the length, and the value.
 fn find_next_data_block(
     last_index: usize,
     expanded_mem: &[Option<u64>],
@@ -154,8 +179,35 @@ fn checksum(expanded_memory: &[Option<u64>]) -> u64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::problems::input::testing::{assert_text_round_trip, check_round_trips};
     use std::str::FromStr;
 
+    #[test]
+    fn test_input_round_trip() {
+        assert_text_round_trip::<DiskMap>("2333133121414131402");
+        check_round_trips(9, |rng| DiskMap {
+            lengths: (0..rng.range(1, 40))
+                .map(|_| rng.range(0, 10) as u8)
+                .collect(),
+        });
+    }
+
+    #[test]
+    fn test_validate() {
+        let data: Vec<String> = "12345\n12a45".lines().map(String::from).collect();
+        assert_eq!(
+            vec![Diagnostic::at(
+                1,
+                "Invalid digit 'a' at column 3.".to_owned()
+            ),],
+            DiskMap::validate(&data)
+        );
+        assert_eq!(
+            vec![Diagnostic::input("The disk map is empty.".to_owned())],
+            DiskMap::validate(&[])
+        );
+    }
+
     #[test]
     fn test_example_data() {
         let mut data = Vec::new();
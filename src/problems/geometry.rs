@@ -0,0 +1,143 @@
+use std::iter;
+
+use crate::{difference_2i, increment_2d_index, Vec2i, Vec2u};
+
+/// Orthogonal directions: up, down, left and right.
+pub const DIRECTIONS_4: [Vec2i; 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+/// Orthogonal and diagonal directions, clockwise starting at right.
+pub const DIRECTIONS_8: [Vec2i; 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+];
+
+/// Take a single step in the direction, if the new position is inside the bounds (rows, cols).
+#[inline]
+pub fn step_in_bounds(pos: Vec2u, dir: Vec2i, bounds: Vec2u) -> Option<Vec2u> {
+    increment_2d_index(pos.0, pos.1, dir.0, dir.1, 1)
+        .filter(|next| next.0 < bounds.0 && next.1 < bounds.1)
+}
+
+/// The cells from the start in the direction, excluding the start, until leaving the bounds.
+/// A zero direction gives no cells.
+pub fn ray(start: Vec2u, dir: Vec2i, bounds: Vec2u) -> impl Iterator<Item = Vec2u> {
+    let moving = dir != (0, 0);
+    iter::successors(Some(start), move |&pos| step_in_bounds(pos, dir, bounds))
+        .skip(1)
+        .take_while(move |_| moving)
+}
+
+/// The cells inside the bounds on the line through two points, spaced by the difference between them.
+/// Both points are included. If the points are equal, only the point itself is given.
+pub fn line_through(first: Vec2u, second: Vec2u, bounds: Vec2u) -> impl Iterator<Item = Vec2u> {
+    let (d_i, d_j) = difference_2i(first, second).unwrap_or((0, 0));
+    let backwards: Vec<Vec2u> = ray(first, (-d_i, -d_j), bounds).collect();
+    let inside = first.0 < bounds.0 && first.1 < bounds.1;
+
+    backwards
+        .into_iter()
+        .rev()
+        .chain(inside.then_some(first))
+        .chain(ray(first, (d_i, d_j), bounds))
+}
+
+/// The cells inside the bounds within a Manhattan distance of the center, with their distance.
+pub fn manhattan_diamond(
+    center: Vec2u,
+    radius: usize,
+    bounds: Vec2u,
+) -> impl Iterator<Item = (Vec2u, usize)> {
+    let first_row = center.0.saturating_sub(radius);
+    let last_row = center
+        .0
+        .saturating_add(radius)
+        .min(bounds.0.saturating_sub(1));
+    (first_row..=last_row)
+        .filter(move |_| bounds.0 > 0 && bounds.1 > 0)
+        .flat_map(move |i| {
+            let remaining = radius - i.abs_diff(center.0);
+            let first_col = center.1.saturating_sub(remaining);
+            let last_col = center
+                .1
+                .saturating_add(remaining)
+                .min(bounds.1.saturating_sub(1));
+            (first_col..=last_col)
+                .map(move |j| ((i, j), i.abs_diff(center.0) + j.abs_diff(center.1)))
+        })
+}
+
+/// The orthogonal neighbours of a cell inside the bounds.
+pub fn neighbours_4(pos: Vec2u, bounds: Vec2u) -> impl Iterator<Item = Vec2u> {
+    DIRECTIONS_4
+        .into_iter()
+        .filter_map(move |dir| step_in_bounds(pos, dir, bounds))
+}
+
+/// The orthogonal and diagonal neighbours of a cell inside the bounds.
+pub fn neighbours_8(pos: Vec2u, bounds: Vec2u) -> impl Iterator<Item = Vec2u> {
+    DIRECTIONS_8
+        .into_iter()
+        .filter_map(move |dir| step_in_bounds(pos, dir, bounds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rays_and_lines() {
+        assert_eq!(
+            vec![(2, 3), (3, 4)],
+            ray((1, 2), (1, 1), (4, 5)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(0, 2)],
+            ray((1, 2), (-1, 0), (4, 5)).collect::<Vec<_>>()
+        );
+        assert_eq!(0, ray((1, 2), (0, 0), (4, 5)).count());
+        assert_eq!(0, ray((3, 4), (0, 1), (4, 5)).count());
+
+        assert_eq!(
+            vec![(0, 0), (1, 2), (2, 4), (3, 6)],
+            line_through((1, 2), (2, 4), (4, 7)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(3, 6), (2, 4), (1, 2), (0, 0)],
+            line_through((2, 4), (1, 2), (4, 7)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(1, 1)],
+            line_through((1, 1), (1, 1), (4, 7)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_diamond_and_neighbours() {
+        let diamond: Vec<(Vec2u, usize)> = manhattan_diamond((1, 1), 2, (3, 10)).collect();
+        assert_eq!(10, diamond.len());
+        assert!(diamond.contains(&((1, 1), 0)));
+        assert!(diamond.contains(&((1, 3), 2)));
+        assert!(diamond.contains(&((0, 0), 2)));
+        assert!(!diamond.iter().any(|&(pos, _)| pos == (0, 3)));
+
+        // Without bounds getting in the way, the diamond has 2r(r+1)+1 cells
+        assert_eq!(
+            2 * 20 * 21 + 1,
+            manhattan_diamond((50, 50), 20, (100, 100)).count()
+        );
+        assert_eq!(0, manhattan_diamond((0, 0), 2, (0, 0)).count());
+
+        assert_eq!(
+            vec![(1, 0), (0, 1)],
+            neighbours_4((0, 0), (3, 3)).collect::<Vec<_>>()
+        );
+        assert_eq!(8, neighbours_8((1, 1), (3, 3)).count());
+        assert_eq!(3, neighbours_8((2, 2), (3, 3)).count());
+    }
+}
@@ -0,0 +1,247 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::render::{Canvas, Highlight};
+
+/// A colour as red, green and blue components.
+pub type Rgb = [u8; 3];
+
+pub const WHITE: Rgb = [255, 255, 255];
+pub const BLACK: Rgb = [0, 0, 0];
+pub const WALL: Rgb = [60, 60, 60];
+pub const VISITED: Rgb = [90, 140, 230];
+pub const PATH: Rgb = [240, 190, 40];
+pub const MARKER: Rgb = [220, 40, 40];
+
+/// Fill colours cycled through for regions.
+const REGION_COLOURS: &[Rgb] = &[
+    [230, 80, 80],
+    [90, 190, 90],
+    [230, 200, 70],
+    [80, 120, 220],
+    [190, 90, 200],
+    [70, 190, 200],
+    [240, 140, 60],
+    [150, 200, 60],
+];
+
+/// Supported netpbm image formats.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageFormat {
+    /// Binary colour image (P6).
+    Ppm,
+    /// Binary black and white image (P4). Every pixel that is not white is drawn black.
+    Pbm,
+}
+
+impl ImageFormat {
+    /// The file extension for the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Pbm => "pbm",
+        }
+    }
+}
+
+/// A colour image stored row by row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    /// Create an image filled with a single colour.
+    pub fn new(width: usize, height: usize, fill: Rgb) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![fill; width * height],
+        }
+    }
+
+    /// Create an image from a canvas, with one pixel per cell.
+    pub fn from_canvas(canvas: &Canvas) -> Image {
+        let (num_rows, num_cols) = canvas.size();
+        let mut image = Image::new(num_cols, num_rows, WHITE);
+        for i in 0..num_rows {
+            for j in 0..num_cols {
+                let colour = match canvas.get_highlight((i, j)) {
+                    Some(Highlight::Visited) => VISITED,
+                    Some(Highlight::Path) => PATH,
+                    Some(Highlight::Marker) => MARKER,
+                    Some(Highlight::Region(index)) => REGION_COLOURS[index % REGION_COLOURS.len()],
+                    None => match canvas.get_char((i, j)) {
+                        Some('.') | Some(' ') | None => WHITE,
+                        Some('#') => WALL,
+                        Some(_) => BLACK,
+                    },
+                };
+                image.set(j, i, colour);
+            }
+        }
+
+        image
+    }
+
+    /// The size of the image as (width, height).
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Get the colour of a pixel, if inside the image.
+    pub fn get(&self, x: usize, y: usize) -> Option<Rgb> {
+        (x < self.width && y < self.height).then(|| self.pixels[y * self.width + x])
+    }
+
+    /// Set the colour of a pixel. Pixels outside the image are ignored.
+    pub fn set(&mut self, x: usize, y: usize, colour: Rgb) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = colour;
+        }
+    }
+
+    /// Scale the image up, drawing each pixel as a square block.
+    pub fn scaled(&self, factor: usize) -> Image {
+        let factor = factor.max(1);
+        let mut image = Image::new(self.width * factor, self.height * factor, WHITE);
+        for y in 0..image.height {
+            for x in 0..image.width {
+                image.pixels[y * image.width + x] =
+                    self.pixels[(y / factor) * self.width + x / factor];
+            }
+        }
+
+        image
+    }
+
+    /// Encode the image in the given format.
+    pub fn encode(&self, format: ImageFormat) -> Vec<u8> {
+        match format {
+            ImageFormat::Ppm => self.encode_ppm(),
+            ImageFormat::Pbm => self.encode_pbm(),
+        }
+    }
+
+    /// Encode as a binary PPM (P6) image.
+    fn encode_ppm(&self) -> Vec<u8> {
+        let mut data = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        data.extend(self.pixels.iter().flatten());
+        data
+    }
+
+    /// Encode as a binary PBM (P4) image, with each row padded to whole bytes.
+    fn encode_pbm(&self) -> Vec<u8> {
+        let mut data = format!("P4\n{} {}\n", self.width, self.height).into_bytes();
+        for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            for byte_pixels in row.chunks(8) {
+                let byte = byte_pixels
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &colour)| colour != WHITE)
+                    .fold(0u8, |byte, (bit, _)| byte | (0x80 >> bit));
+                data.push(byte);
+            }
+        }
+        data
+    }
+
+    /// Write the image to a file in the given format.
+    pub fn write(&self, path: &Path, format: ImageFormat) -> Result<(), String> {
+        fs::write(path, self.encode(format))
+            .map_err(|_| format!("Failed to write image to {}", path.display()))
+    }
+}
+
+/// Writes a numbered sequence of frames to a folder.
+pub struct FrameWriter {
+    dir: PathBuf,
+    scale: usize,
+    count: usize,
+}
+
+impl FrameWriter {
+    /// Create a writer for the folder, creating the folder if needed. Frames are scaled up by `scale`.
+    pub fn new(dir: &Path, scale: usize) -> Result<FrameWriter, String> {
+        fs::create_dir_all(dir)
+            .map_err(|_| format!("Failed to create folder {}", dir.display()))?;
+        Ok(FrameWriter {
+            dir: dir.to_path_buf(),
+            scale,
+            count: 0,
+        })
+    }
+
+    /// Write the next frame, named `frame_00000.ppm`, `frame_00001.ppm`, etc.
+    pub fn write(&mut self, image: &Image, format: ImageFormat) -> Result<(), String> {
+        let path = self
+            .dir
+            .join(format!("frame_{:05}.{}", self.count, format.extension()));
+        image.scaled(self.scale).write(&path, format)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// The number of frames written so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_ppm_and_pbm() {
+        let mut image = Image::new(9, 2, WHITE);
+        image.set(0, 0, BLACK);
+        image.set(8, 0, MARKER);
+        image.set(1, 1, PATH);
+        image.set(20, 20, BLACK);
+
+        let ppm = image.encode(ImageFormat::Ppm);
+        let header = b"P6\n9 2\n255\n";
+        assert_eq!(header, &ppm[..header.len()]);
+        assert_eq!(header.len() + 9 * 2 * 3, ppm.len());
+        assert_eq!(&MARKER, &ppm[header.len() + 8 * 3..header.len() + 9 * 3]);
+
+        let pbm = image.encode(ImageFormat::Pbm);
+        assert_eq!(b"P4\n9 2\n".to_vec(), pbm[..7].to_vec());
+        assert_eq!(
+            vec![0b1000_0000, 0b1000_0000, 0b0100_0000, 0],
+            pbm[7..].to_vec()
+        );
+    }
+
+    #[test]
+    fn test_from_canvas_and_frames() {
+        let data: Vec<String> = ["#.", ".S"].iter().map(|s| s.to_string()).collect();
+        let mut canvas = Canvas::from_lines(&data);
+        canvas.highlight((1, 1), Highlight::Marker);
+        canvas.highlight((0, 1), Highlight::Path);
+
+        let image = Image::from_canvas(&canvas).scaled(2);
+        assert_eq!((4, 4), image.size());
+        assert_eq!(Some(WALL), image.get(1, 1));
+        assert_eq!(Some(PATH), image.get(2, 0));
+        assert_eq!(Some(WHITE), image.get(0, 3));
+        assert_eq!(Some(MARKER), image.get(3, 3));
+
+        let dir = std::env::temp_dir().join(format!("aoc24-frames-{}", std::process::id()));
+        let mut writer = FrameWriter::new(&dir, 1).unwrap();
+        writer.write(&image, ImageFormat::Ppm).unwrap();
+        writer.write(&image, ImageFormat::Pbm).unwrap();
+        let first = fs::read(dir.join("frame_00000.ppm")).unwrap();
+        let second_exists = dir.join("frame_00001.pbm").is_file();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(2, writer.count());
+        assert_eq!(image.encode(ImageFormat::Ppm), first);
+        assert!(second_exists);
+    }
+}
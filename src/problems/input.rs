@@ -0,0 +1,380 @@
+use std::fmt::{self, Display};
+
+use crate::Vec2u;
+
+/// A parsed puzzle input, that can be printed back in the puzzle text format with [`Display`].
+pub trait PuzzleInput: Sized + Display {
+    /// Parse the lines of the puzzle input.
+    fn parse(input_data: &[String]) -> Result<Self, String>;
+
+    /// List all structural problems in the lines that would lead to a wrong answer.
+    fn validate(_input_data: &[String]) -> Vec<Diagnostic> {
+        Vec::new()
+    }
+
+    /// Validate the lines before parsing them, failing with every problem found.
+    fn parse_checked(input_data: &[String]) -> Result<Self, String> {
+        let mut diagnostics = Self::validate(input_data);
+        diagnostics.sort_by_key(|diagnostic| diagnostic.line);
+        if !diagnostics.is_empty() {
+            return Err(format_diagnostics(&diagnostics));
+        }
+
+        Self::parse(input_data)
+    }
+}
+
+/// A structural problem in the input, with the line it was found on if it belongs to one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// Line number, starting at 1.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic for the line at the index, starting at 0.
+    pub fn at(index: usize, message: String) -> Diagnostic {
+        Diagnostic {
+            line: Some(index + 1),
+            message,
+        }
+    }
+
+    /// Create a diagnostic for the input as a whole.
+    pub fn input(message: String) -> Diagnostic {
+        Diagnostic {
+            line: None,
+            message,
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "input: {}", self.message),
+        }
+    }
+}
+
+/// Format the diagnostics as an error message, with one problem per line.
+pub fn format_diagnostics(diagnostics: &[Diagnostic]) -> String {
+    let mut message = format!("Found {} problems in the input:", diagnostics.len());
+    for diagnostic in diagnostics {
+        message.push_str(&format!("\n  {}", diagnostic));
+    }
+    message
+}
+
+/// Split the lines into the sections separated by empty lines, with the index of their first line.
+pub fn sections(input_data: &[String]) -> Vec<(usize, &[String])> {
+    let mut sections = Vec::new();
+    let mut start = 0;
+    for (i, line) in input_data.iter().enumerate() {
+        if line.is_empty() {
+            sections.push((start, &input_data[start..i]));
+            start = i + 1;
+        }
+    }
+    sections.push((start, &input_data[start..]));
+
+    sections
+}
+
+/// Check every line with the parser, where the first line has the index `offset`.
+pub fn check_lines<T, F>(lines: &[String], offset: usize, parse_line: F) -> Vec<Diagnostic>
+where
+    F: Fn(&str) -> Result<T, String>,
+{
+    lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            parse_line(line)
+                .err()
+                .map(|err| Diagnostic::at(offset + i, err))
+        })
+        .collect()
+}
+
+/// Check that the rows form a non-empty rectangular grid of valid characters, where the first row
+/// has the index `offset`.
+pub fn check_grid<F>(rows: &[String], offset: usize, is_valid: F) -> Vec<Diagnostic>
+where
+    F: Fn(char) -> bool,
+{
+    let Some(first_row) = rows.first() else {
+        return vec![Diagnostic::input("The grid has no rows.".to_owned())];
+    };
+
+    let num_cols = first_row.chars().count();
+    let mut diagnostics = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        let len = row.chars().count();
+        if len != num_cols {
+            diagnostics.push(Diagnostic::at(
+                offset + i,
+                format!("Row has {} cells, expected {}.", len, num_cols),
+            ));
+        }
+        for (j, c) in row.chars().enumerate().filter(|&(_, c)| !is_valid(c)) {
+            diagnostics.push(Diagnostic::at(
+                offset + i,
+                format!("Invalid character '{}' at column {}.", c, j + 1),
+            ));
+        }
+    }
+
+    diagnostics
+}
+
+/// Check that exactly one of the target characters is in the rows, where the first row has the
+/// index `offset`.
+pub fn check_unique(
+    rows: &[String],
+    offset: usize,
+    targets: &[char],
+    name: &str,
+) -> Vec<Diagnostic> {
+    let found: Vec<(usize, usize)> = rows
+        .iter()
+        .enumerate()
+        .flat_map(|(i, row)| {
+            row.chars()
+                .enumerate()
+                .filter(|(_, c)| targets.contains(c))
+                .map(move |(j, _)| (i, j))
+        })
+        .collect();
+
+    match found.split_first() {
+        None => vec![Diagnostic::input(format!("No {} found.", name))],
+        Some((first, rest)) => rest
+            .iter()
+            .map(|&(i, j)| {
+                Diagnostic::at(
+                    offset + i,
+                    format!(
+                        "Found another {} at column {}, the first is on line {}.",
+                        name,
+                        j + 1,
+                        offset + first.0 + 1
+                    ),
+                )
+            })
+            .collect(),
+    }
+}
+
+/// Check that every cell on the edge of the grid is the border character, so paths through the
+/// grid can not leave it. The first row has the index `offset`.
+pub fn check_border(rows: &[String], offset: usize, border: char) -> Vec<Diagnostic> {
+    let last_row = rows.len().saturating_sub(1);
+    rows.iter()
+        .enumerate()
+        .flat_map(|(i, row)| {
+            let last_col = row.chars().count().saturating_sub(1);
+            row.chars()
+                .enumerate()
+                .filter(move |&(j, c)| {
+                    (i == 0 || i == last_row || j == 0 || j == last_col) && c != border
+                })
+                .map(move |(j, _)| {
+                    Diagnostic::at(
+                        offset + i,
+                        format!("Expected '{}' on the edge at column {}.", border, j + 1),
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Print the value and split it into lines, the same way input files are read.
+pub fn to_lines<T: Display>(value: &T) -> Vec<String> {
+    value.to_string().lines().map(String::from).collect()
+}
+
+/// Get the size (rows, cols) of a rectangular grid of characters.
+pub fn grid_size(input_data: &[String]) -> Result<Vec2u, String> {
+    let num_cols = input_data
+        .first()
+        .ok_or("The grid has no rows.")?
+        .chars()
+        .count();
+    if let Some(i) = input_data
+        .iter()
+        .position(|row| row.chars().count() != num_cols)
+    {
+        return Err(format!(
+            "Row {} has {} cells, expected {}.",
+            i,
+            input_data[i].chars().count(),
+            num_cols
+        ));
+    }
+
+    Ok((input_data.len(), num_cols))
+}
+
+/// Iterate over all cells of a grid of characters, with their position.
+pub fn grid_cells(input_data: &[String]) -> impl Iterator<Item = (Vec2u, char)> + '_ {
+    input_data
+        .iter()
+        .enumerate()
+        .flat_map(|(i, row)| row.chars().enumerate().map(move |(j, c)| ((i, j), c)))
+}
+
+/// Write a grid of the size, with the character for each cell given by the function.
+pub fn write_grid<F>(f: &mut fmt::Formatter, size: Vec2u, cell: F) -> fmt::Result
+where
+    F: Fn(Vec2u) -> char,
+{
+    for i in 0..size.0 {
+        let row: String = (0..size.1).map(|j| cell((i, j))).collect();
+        writeln!(f, "{}", row)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_checks() {
+        let grid = lines("#.S.\n#S\n..x.");
+        let diagnostics = check_grid(&grid, 2, |c| ".#S".contains(c));
+        assert_eq!(
+            vec![
+                Diagnostic::at(3, "Row has 2 cells, expected 4.".to_owned()),
+                Diagnostic::at(4, "Invalid character 'x' at column 3.".to_owned()),
+            ],
+            diagnostics
+        );
+        assert_eq!(
+            "line 4: Row has 2 cells, expected 4.",
+            diagnostics[0].to_string()
+        );
+
+        assert_eq!(
+            vec![Diagnostic::at(
+                1,
+                "Found another start at column 2, the first is on line 1.".to_owned()
+            )],
+            check_unique(&grid, 0, &['S'], "start")
+        );
+        assert_eq!(
+            vec![Diagnostic::input("No end found.".to_owned())],
+            check_unique(&grid, 0, &['E'], "end")
+        );
+        assert_eq!(
+            vec![
+                Diagnostic::at(0, "Expected '#' on the edge at column 2.".to_owned()),
+                Diagnostic::at(0, "Expected '#' on the edge at column 3.".to_owned()),
+                Diagnostic::at(0, "Expected '#' on the edge at column 4.".to_owned()),
+                Diagnostic::at(1, "Expected '#' on the edge at column 2.".to_owned()),
+                Diagnostic::at(2, "Expected '#' on the edge at column 1.".to_owned()),
+                Diagnostic::at(2, "Expected '#' on the edge at column 2.".to_owned()),
+                Diagnostic::at(2, "Expected '#' on the edge at column 3.".to_owned()),
+                Diagnostic::at(2, "Expected '#' on the edge at column 4.".to_owned()),
+            ],
+            check_border(&grid, 0, '#')
+        );
+
+        let numbers = lines("1\ntwo\n3");
+        let diagnostics = check_lines(&numbers, 0, |line| {
+            line.parse::<u32>()
+                .map_err(|_| format!("Not a number: {}", line))
+        });
+        assert_eq!(
+            vec![Diagnostic::at(1, "Not a number: two".to_owned())],
+            diagnostics
+        );
+    }
+
+    #[test]
+    fn test_sections() {
+        let data = lines("a\nb\n\nc\n\n\nd");
+        let found: Vec<(usize, usize)> = sections(&data)
+            .iter()
+            .map(|(start, lines)| (*start, lines.len()))
+            .collect();
+        assert_eq!(vec![(0, 2), (3, 1), (5, 0), (6, 1)], found);
+    }
+}
+
+#[cfg(test)]
+pub mod testing {
+    use std::fmt::Debug;
+
+    use super::{to_lines, PuzzleInput};
+
+    /// Number of random values checked by [`check_round_trips`].
+    const NUM_CASES: usize = 50;
+
+    /// A small xorshift generator, for generating test cases without extra dependencies.
+    pub struct Rng(u64);
+
+    impl Rng {
+        /// Create a generator from a non-zero seed.
+        pub fn new(seed: u64) -> Rng {
+            Rng(seed.max(1))
+        }
+
+        /// Generate the next random value.
+        pub fn next_u64(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        /// Generate a value in the range `low..high`.
+        pub fn range(&mut self, low: usize, high: usize) -> usize {
+            low + (self.next_u64() % (high - low) as u64) as usize
+        }
+
+        /// Generate true with the given probability.
+        pub fn chance(&mut self, probability: f64) -> bool {
+            (self.next_u64() % 1_000_000) as f64 / 1_000_000. < probability
+        }
+
+        /// Pick a random item from the slice.
+        pub fn choose<T: Copy>(&mut self, items: &[T]) -> T {
+            items[self.range(0, items.len())]
+        }
+    }
+
+    /// Check that printing and parsing the value gives back an equal value.
+    pub fn assert_round_trip<T: PuzzleInput + PartialEq + Debug>(value: &T) {
+        let lines = to_lines(value);
+        let parsed = T::parse(&lines)
+            .unwrap_or_else(|err| panic!("Failed to parse {:?} with error: {}", lines, err));
+        assert_eq!(value, &parsed, "Round trip failed for {:?}", lines);
+    }
+
+    /// Check that parsing the text and printing it gives back the same text.
+    pub fn assert_text_round_trip<T: PuzzleInput>(text: &str) {
+        let lines: Vec<String> = text.lines().map(String::from).collect();
+        let parsed = T::parse(&lines).unwrap();
+        assert_eq!(lines, to_lines(&parsed));
+    }
+
+    /// Check the round trip for many values from the generator.
+    pub fn check_round_trips<T, F>(seed: u64, generate: F)
+    where
+        T: PuzzleInput + PartialEq + Debug,
+        F: Fn(&mut Rng) -> T,
+    {
+        let mut rng = Rng::new(seed);
+        for _ in 0..NUM_CASES {
+            assert_round_trip(&generate(&mut rng));
+        }
+    }
+}
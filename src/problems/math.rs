@@ -0,0 +1,185 @@
+/// Count the number of decimal digits in a value. Zero has a single digit.
+pub fn num_digits(val: u64) -> u32 {
+    val.checked_ilog10().unwrap_or(0) + 1
+}
+
+/// Split a value with an even number of digits into the first and second half of its digits.
+/// Returns [`None`] for an odd number of digits.
+pub fn split_digits(val: u64) -> Option<(u64, u64)> {
+    let digits = num_digits(val);
+    if !digits.is_multiple_of(2) {
+        return None;
+    }
+
+    let divisor = 10u64.pow(digits / 2);
+    Some((val / divisor, val % divisor))
+}
+
+/// Concatenate the digits of two values, returning [`None`] on overflow.
+pub fn concat_digits(lhs: u64, rhs: u64) -> Option<u64> {
+    lhs.checked_mul(10u64.checked_pow(num_digits(rhs))?)?
+        .checked_add(rhs)
+}
+
+/// Greatest common divisor of two values.
+pub fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Least common multiple of two values, returning [`None`] on overflow.
+pub fn lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// Extended Euclidean algorithm. Returns (g, x, y) where g = gcd(a, b) >= 0 and a * x + b * y = g.
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// Modular inverse of a value, in the range `0..modulus`. Returns [`None`] if it does not exist.
+pub fn mod_inverse(val: i64, modulus: i64) -> Option<i64> {
+    if modulus <= 0 {
+        return None;
+    }
+    let (g, x, _) = extended_gcd(val.rem_euclid(modulus), modulus);
+    (g == 1).then(|| x.rem_euclid(modulus))
+}
+
+/// Solve a system of congruences x = r (mod m) given as (r, m) pairs, with the Chinese remainder
+/// theorem. The moduli do not have to be coprime. Returns the smallest non-negative solution and
+/// the modulus of the combined congruence, or [`None`] if there is no solution.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let mut result: (i128, i128) = (0, 1);
+    for &(residue, modulus) in congruences {
+        if modulus <= 0 {
+            return None;
+        }
+        let (residue, modulus) = (residue as i128, modulus as i128);
+        let (g, p, _) = extended_gcd(result.1 as i64, modulus as i64);
+        let g = g as i128;
+        let diff = residue - result.0;
+        if diff % g != 0 {
+            return None;
+        }
+
+        let combined_modulus = result.1 / g * modulus;
+        let k = (diff / g * p as i128).rem_euclid(modulus / g);
+        result = (
+            (result.0 + k * result.1).rem_euclid(combined_modulus),
+            combined_modulus,
+        );
+    }
+
+    Some((i64::try_from(result.0).ok()?, i64::try_from(result.1).ok()?))
+}
+
+/// Solve the system `mat * x = b` for two variables exactly, using Cramer's rule.
+/// Returns [`None`] if the system is singular or the solution is not integer.
+pub fn solve_2x2(mat: &[[i64; 2]; 2], b: &[i64; 2]) -> Option<[i64; 2]> {
+    let det = mat[0][0] as i128 * mat[1][1] as i128 - mat[0][1] as i128 * mat[1][0] as i128;
+    if det == 0 {
+        return None;
+    }
+
+    let num_0 = mat[1][1] as i128 * b[0] as i128 - mat[0][1] as i128 * b[1] as i128;
+    let num_1 = mat[0][0] as i128 * b[1] as i128 - mat[1][0] as i128 * b[0] as i128;
+    if num_0 % det != 0 || num_1 % det != 0 {
+        return None;
+    }
+
+    Some([
+        i64::try_from(num_0 / det).ok()?,
+        i64::try_from(num_1 / det).ok()?,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digits() {
+        assert_eq!(1, num_digits(0));
+        assert_eq!(1, num_digits(9));
+        assert_eq!(2, num_digits(10));
+        assert_eq!(20, num_digits(u64::MAX));
+
+        assert_eq!(Some((10, 0)), split_digits(1000));
+        assert_eq!(None, split_digits(253));
+        assert_eq!(Some((1, 7)), split_digits(17));
+        assert_eq!(Some((2024, 1)), split_digits(20240001));
+
+        assert_eq!(Some(156), concat_digits(15, 6));
+        assert_eq!(Some(100), concat_digits(10, 0));
+        assert_eq!(Some(7), concat_digits(0, 7));
+        assert_eq!(None, concat_digits(u64::MAX, 1));
+    }
+
+    #[test]
+    fn test_gcd_lcm_and_inverse() {
+        assert_eq!(6, gcd(48, 18));
+        assert_eq!(5, gcd(0, 5));
+        assert_eq!(Some(144), lcm(48, 18));
+        assert_eq!(Some(0), lcm(0, 3));
+        assert_eq!(None, lcm(u64::MAX, u64::MAX - 1));
+
+        for (a, b) in [(240, 46), (-240, 46), (17, -5), (0, 7), (7, 0)] {
+            let (g, x, y) = extended_gcd(a, b);
+            assert_eq!(gcd(a.unsigned_abs(), b.unsigned_abs()) as i64, g);
+            assert_eq!(g, a * x + b * y);
+        }
+
+        assert_eq!(Some(4), mod_inverse(3, 11));
+        assert_eq!(Some(7), mod_inverse(-3, 11));
+        assert_eq!(None, mod_inverse(6, 9));
+        assert_eq!(None, mod_inverse(1, 0));
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(Some((23, 105)), crt(&[(2, 3), (3, 5), (2, 7)]));
+        assert_eq!(Some((11, 12)), crt(&[(3, 4), (5, 6)]));
+        assert_eq!(None, crt(&[(1, 4), (2, 6)]));
+        assert_eq!(Some((0, 1)), crt(&[]));
+
+        // Every pair of residues for coprime moduli has a single solution
+        for a in 0..101 {
+            let b = (a * 7) % 103;
+            let (x, m) = crt(&[(a, 101), (b, 103)]).unwrap();
+            assert_eq!(101 * 103, m);
+            assert_eq!((a, b), (x % 101, x % 103));
+        }
+    }
+
+    #[test]
+    fn test_solve_2x2() {
+        assert_eq!(
+            Some([80, 40]),
+            solve_2x2(&[[94, 22], [34, 67]], &[8400, 5400])
+        );
+        assert_eq!(None, solve_2x2(&[[26, 67], [66, 21]], &[12748, 12176]));
+        assert_eq!(None, solve_2x2(&[[1, 2], [2, 4]], &[3, 6]));
+        assert_eq!(Some([-1, 2]), solve_2x2(&[[1, 1], [1, -1]], &[1, -3]));
+    }
+}
@@ -0,0 +1,196 @@
+use crate::Vec2u;
+
+/// ANSI escape sequence to reset the style.
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// Background colours cycled through for regions.
+const REGION_COLOURS: &[u8] = &[41, 42, 43, 44, 45, 46, 101, 102, 103, 104, 105, 106];
+
+/// Kinds of overlays that can be drawn on top of a grid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Highlight {
+    /// A cell that was visited during a search or simulation.
+    Visited,
+    /// A cell on a path of interest, such as the shortest path.
+    Path,
+    /// A cell belonging to a region, with the index of the region.
+    Region(usize),
+    /// A point of interest, such as a start, end or robot.
+    Marker,
+}
+
+impl Highlight {
+    /// Char used in place of an empty cell when rendering without colours.
+    fn plain_char(&self) -> Option<char> {
+        match self {
+            Highlight::Visited => Some('X'),
+            Highlight::Path => Some('O'),
+            Highlight::Region(_) | Highlight::Marker => None,
+        }
+    }
+
+    /// ANSI escape sequence for the highlight.
+    fn ansi_code(&self) -> String {
+        match self {
+            Highlight::Visited => "\x1b[34;1m".to_owned(),
+            Highlight::Path => "\x1b[30;43m".to_owned(),
+            Highlight::Region(i) => {
+                format!("\x1b[30;{}m", REGION_COLOURS[i % REGION_COLOURS.len()])
+            }
+            Highlight::Marker => "\x1b[31;1m".to_owned(),
+        }
+    }
+}
+
+/// A grid of chars with highlights, that can be rendered to text for the terminal.
+pub struct Canvas {
+    chars: Vec<Vec<char>>,
+    highlights: Vec<Vec<Option<Highlight>>>,
+}
+
+impl Canvas {
+    /// Create an empty canvas of the given size (rows, cols), filled with a char.
+    pub fn new(size: Vec2u, fill: char) -> Canvas {
+        Canvas {
+            chars: vec![vec![fill; size.1]; size.0],
+            highlights: vec![vec![None; size.1]; size.0],
+        }
+    }
+
+    /// Create a canvas from the lines of a puzzle input. Short rows are padded with spaces.
+    pub fn from_lines(data: &[String]) -> Canvas {
+        let num_cols = data
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut canvas = Canvas::new((data.len(), num_cols), ' ');
+        for (i, line) in data.iter().enumerate() {
+            for (j, c) in line.chars().enumerate() {
+                canvas.chars[i][j] = c;
+            }
+        }
+
+        canvas
+    }
+
+    /// The size of the canvas as (rows, cols).
+    pub fn size(&self) -> Vec2u {
+        (
+            self.chars.len(),
+            self.chars.first().map_or(0, |row| row.len()),
+        )
+    }
+
+    /// Get the char at a position, if inside the canvas.
+    pub fn get_char(&self, pos: Vec2u) -> Option<char> {
+        self.chars.get(pos.0)?.get(pos.1).copied()
+    }
+
+    /// Get the highlight at a position, if any.
+    pub fn get_highlight(&self, pos: Vec2u) -> Option<Highlight> {
+        *self.highlights.get(pos.0)?.get(pos.1)?
+    }
+
+    /// Set the char at a position. Positions outside the canvas are ignored.
+    pub fn set_char(&mut self, pos: Vec2u, c: char) {
+        if let Some(cell) = self.chars.get_mut(pos.0).and_then(|row| row.get_mut(pos.1)) {
+            *cell = c;
+        }
+    }
+
+    /// Highlight a single cell. Positions outside the canvas are ignored.
+    pub fn highlight(&mut self, pos: Vec2u, highlight: Highlight) {
+        if let Some(cell) = self
+            .highlights
+            .get_mut(pos.0)
+            .and_then(|row| row.get_mut(pos.1))
+        {
+            *cell = Some(highlight);
+        }
+    }
+
+    /// Highlight all cells in the iterator.
+    pub fn overlay<I: IntoIterator<Item = Vec2u>>(&mut self, cells: I, highlight: Highlight) {
+        for pos in cells {
+            self.highlight(pos, highlight);
+        }
+    }
+
+    /// Place a marker char at a position.
+    pub fn mark(&mut self, pos: Vec2u, c: char) {
+        self.set_char(pos, c);
+        self.highlight(pos, Highlight::Marker);
+    }
+
+    /// Render the canvas to text, one line per row.
+    /// Without colours, visited and path cells are drawn over empty `.` cells as `X` and `O`.
+    pub fn render(&self, colour: bool) -> String {
+        let mut text = String::with_capacity(self.chars.len() * (self.size().1 + 1));
+        for (row, highlights) in self.chars.iter().zip(self.highlights.iter()) {
+            let mut current: Option<Highlight> = None;
+            for (&c, &highlight) in row.iter().zip(highlights) {
+                if colour {
+                    if highlight != current {
+                        if current.is_some() {
+                            text.push_str(ANSI_RESET);
+                        }
+                        if let Some(h) = highlight {
+                            text.push_str(&h.ansi_code());
+                        }
+                        current = highlight;
+                    }
+                    text.push(c);
+                } else {
+                    let plain = highlight.and_then(|h| h.plain_char());
+                    text.push(if c == '.' { plain.unwrap_or(c) } else { c });
+                }
+            }
+            if colour && current.is_some() {
+                text.push_str(ANSI_RESET);
+            }
+            text.push('\n');
+        }
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_plain_and_colour() {
+        let data: Vec<String> = ["#..", "...", "..#"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut canvas = Canvas::from_lines(&data);
+        canvas.overlay([(1, 0), (1, 1)], Highlight::Path);
+        canvas.highlight((0, 1), Highlight::Visited);
+        canvas.mark((2, 0), 'S');
+        canvas.highlight((5, 5), Highlight::Marker);
+
+        assert_eq!((3, 3), canvas.size());
+        assert_eq!("#X.\nOO.\nS.#\n", canvas.render(false));
+
+        let coloured = canvas.render(true);
+        assert!(coloured.contains("\x1b[30;43m..\x1b[0m"));
+        assert!(coloured.contains("\x1b[31;1mS\x1b[0m"));
+
+        // Removing the escape sequences gives back the original chars.
+        let stripped = coloured
+            .split('\x1b')
+            .enumerate()
+            .map(|(i, part)| {
+                if i == 0 {
+                    part
+                } else {
+                    &part[part.find('m').unwrap() + 1..]
+                }
+            })
+            .collect::<String>();
+        assert_eq!("#..\n...\nS.#\n", stripped);
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt;
+
+/// A single cell in a report table.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Number(i64),
+    Text(String),
+    /// A fact that does not exist, such as the solution of an unsolvable system.
+    Empty,
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Number(val) => write!(f, "{}", val),
+            Value::Text(text) => write!(f, "{}", text),
+            Value::Empty => write!(f, "-"),
+        }
+    }
+}
+
+impl From<i64> for Value {
+    fn from(val: i64) -> Value {
+        Value::Number(val)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(val: i32) -> Value {
+        Value::Number(val as i64)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(val: u32) -> Value {
+        Value::Number(val as i64)
+    }
+}
+
+/// Values too large for a signed number are kept exactly as text.
+impl From<u64> for Value {
+    fn from(val: u64) -> Value {
+        i64::try_from(val).map_or_else(|_| Value::Text(val.to_string()), Value::Number)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(val: usize) -> Value {
+        i64::try_from(val).map_or_else(|_| Value::Text(val.to_string()), Value::Number)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(val: Option<T>) -> Value {
+        val.map_or(Value::Empty, Into::into)
+    }
+}
+
+impl From<String> for Value {
+    fn from(text: String) -> Value {
+        Value::Text(text)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(text: &str) -> Value {
+        Value::Text(text.to_owned())
+    }
+}
+
+/// A titled table of facts, with one value per column in each row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Section {
+    pub title: String,
+    pub columns: Vec<&'static str>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl Section {
+    /// Create an empty section with the columns.
+    pub fn new(title: &str, columns: &[&'static str]) -> Section {
+        Section {
+            title: title.to_owned(),
+            columns: columns.to_vec(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Add a row. Panics if the number of values does not match the columns.
+    pub fn push_row(&mut self, row: Vec<Value>) {
+        assert_eq!(
+            self.columns.len(),
+            row.len(),
+            "Row does not match the columns of section {}",
+            self.title
+        );
+        self.rows.push(row);
+    }
+}
+
+/// The intermediate facts behind the answers of a day, for explaining how they were found.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Report {
+    pub sections: Vec<Section>,
+}
+
+impl Report {
+    /// Create a report from the sections.
+    pub fn new(sections: Vec<Section>) -> Report {
+        Report { sections }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unsigned_values() {
+        assert_eq!(Value::Number(i64::MAX), Value::from(i64::MAX as u64));
+        assert_eq!(
+            Value::Text("18446744073709551615".to_owned()),
+            Value::from(u64::MAX)
+        );
+        assert_eq!(Value::from(usize::MAX as u64), Value::from(usize::MAX));
+    }
+}
@@ -0,0 +1,296 @@
+/// A simulation made of a state and a step function.
+pub trait Simulation {
+    /// A comparable copy of the state, used for history and cycle detection.
+    type Snapshot: Clone + PartialEq;
+
+    /// Advance the simulation by one step.
+    fn step(&mut self) -> Result<(), String>;
+
+    /// Take a snapshot of the current state.
+    fn snapshot(&self) -> Self::Snapshot;
+
+    /// Check if the simulation has finished and can not be stepped further.
+    fn is_terminal(&self) -> bool;
+}
+
+/// Why a run of a simulation stopped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    /// The simulation reached a terminal state after the number of steps.
+    Terminal(usize),
+    /// The step limit was reached before a terminal state.
+    StepLimit(usize),
+}
+
+/// A cycle in the sequence of states, starting after `start` steps and repeating every `length` steps.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+/// Runs a simulation with an optional step limit, and optionally records the history of snapshots.
+pub struct SimulationRunner<S: Simulation> {
+    simulation: S,
+    step_limit: Option<usize>,
+    history: Option<Vec<S::Snapshot>>,
+    steps: usize,
+}
+
+impl<S: Simulation> SimulationRunner<S> {
+    /// Create a runner for the simulation, without step limit or history.
+    pub fn new(simulation: S) -> SimulationRunner<S> {
+        SimulationRunner {
+            simulation,
+            step_limit: None,
+            history: None,
+            steps: 0,
+        }
+    }
+
+    /// Stop running after the number of steps.
+    pub fn with_step_limit(mut self, step_limit: usize) -> SimulationRunner<S> {
+        self.step_limit = Some(step_limit);
+        self
+    }
+
+    /// Record a snapshot of the initial state and after every step.
+    pub fn with_history(mut self) -> SimulationRunner<S> {
+        self.history = Some(vec![self.simulation.snapshot()]);
+        self
+    }
+
+    /// Take a single step. Returns false without stepping if terminal or at the step limit.
+    pub fn step(&mut self) -> Result<bool, String> {
+        if self.simulation.is_terminal() || self.step_limit.is_some_and(|limit| self.steps >= limit)
+        {
+            return Ok(false);
+        }
+
+        self.simulation.step()?;
+        self.steps += 1;
+        if let Some(history) = self.history.as_mut() {
+            history.push(self.simulation.snapshot());
+        }
+
+        Ok(true)
+    }
+
+    /// Step until the simulation is terminal or the step limit is reached.
+    pub fn run(&mut self) -> Result<Outcome, String> {
+        while self.step()? {}
+
+        if self.simulation.is_terminal() {
+            Ok(Outcome::Terminal(self.steps))
+        } else {
+            Ok(Outcome::StepLimit(self.steps))
+        }
+    }
+
+    /// The number of steps taken so far.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// The current state of the simulation.
+    pub fn simulation(&self) -> &S {
+        &self.simulation
+    }
+
+    /// The recorded snapshots, starting with the initial state. Empty if history is not recorded.
+    pub fn history(&self) -> &[S::Snapshot] {
+        self.history.as_deref().unwrap_or(&[])
+    }
+
+    /// Get the recorded snapshot after the number of steps.
+    pub fn replay(&self, step: usize) -> Option<&S::Snapshot> {
+        self.history().get(step)
+    }
+
+    /// Consume the runner and return the simulation.
+    pub fn into_inner(self) -> S {
+        self.simulation
+    }
+}
+
+/// Step a simulation, returning false if it is terminal.
+fn advance<S: Simulation>(simulation: &mut S) -> Result<bool, String> {
+    if simulation.is_terminal() {
+        return Ok(false);
+    }
+    simulation.step()?;
+    Ok(true)
+}
+
+/// Find a cycle in the states reachable from the initial state with Brent's algorithm.
+/// Returns [`None`] if a terminal state or the step limit is reached first.
+pub fn detect_cycle_brent<S: Simulation + Clone>(
+    initial: &S,
+    step_limit: Option<usize>,
+) -> Result<Option<Cycle>, String> {
+    let within_limit = |steps: usize| step_limit.is_none_or(|limit| steps <= limit);
+
+    // Find the cycle length by moving the hare, and teleporting the tortoise at powers of two.
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.snapshot();
+    let mut hare = initial.clone();
+    let mut steps = 1;
+    if !advance(&mut hare)? {
+        return Ok(None);
+    }
+    while tortoise != hare.snapshot() {
+        if power == length {
+            tortoise = hare.snapshot();
+            power *= 2;
+            length = 0;
+        }
+        steps += 1;
+        if !within_limit(steps) || !advance(&mut hare)? {
+            return Ok(None);
+        }
+        length += 1;
+    }
+
+    // Find the start of the cycle, with the hare a full cycle ahead of the tortoise.
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    for _ in 0..length {
+        advance(&mut hare)?;
+    }
+    let mut start = 0;
+    while tortoise.snapshot() != hare.snapshot() {
+        advance(&mut tortoise)?;
+        advance(&mut hare)?;
+        start += 1;
+    }
+
+    Ok(Some(Cycle { start, length }))
+}
+
+/// Find a cycle in the states reachable from the initial state with Floyd's algorithm.
+/// Returns [`None`] if a terminal state or the step limit is reached first.
+pub fn detect_cycle_floyd<S: Simulation + Clone>(
+    initial: &S,
+    step_limit: Option<usize>,
+) -> Result<Option<Cycle>, String> {
+    let within_limit = |steps: usize| step_limit.is_none_or(|limit| steps <= limit);
+
+    // Move the hare twice as fast as the tortoise until they meet inside the cycle.
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    let mut steps = 0;
+    loop {
+        steps += 2;
+        if !within_limit(steps) || !advance(&mut hare)? || !advance(&mut hare)? {
+            return Ok(None);
+        }
+        advance(&mut tortoise)?;
+        if tortoise.snapshot() == hare.snapshot() {
+            break;
+        }
+    }
+
+    // Find the start of the cycle by moving a new tortoise from the initial state.
+    let mut tortoise = initial.clone();
+    let mut start = 0;
+    while tortoise.snapshot() != hare.snapshot() {
+        advance(&mut tortoise)?;
+        advance(&mut hare)?;
+        start += 1;
+    }
+
+    // Find the length by walking the cycle once.
+    let cycle_state = tortoise.snapshot();
+    let mut length = 1;
+    advance(&mut hare)?;
+    while hare.snapshot() != cycle_state {
+        advance(&mut hare)?;
+        length += 1;
+    }
+
+    Ok(Some(Cycle { start, length }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Repeatedly applies x -> (x * x + 1) mod m, terminating at zero.
+    #[derive(Clone)]
+    struct Sequence {
+        value: u64,
+        modulus: u64,
+    }
+
+    impl Simulation for Sequence {
+        type Snapshot = u64;
+
+        fn step(&mut self) -> Result<(), String> {
+            self.value = (self.value * self.value + 1) % self.modulus;
+            Ok(())
+        }
+
+        fn snapshot(&self) -> u64 {
+            self.value
+        }
+
+        fn is_terminal(&self) -> bool {
+            self.value == 0
+        }
+    }
+
+    /// Find the cycle by brute force, recording all states.
+    fn brute_force_cycle(value: u64, modulus: u64) -> Option<Cycle> {
+        let mut runner = SimulationRunner::new(Sequence { value, modulus })
+            .with_history()
+            .with_step_limit(2 * modulus as usize);
+        runner.run().unwrap();
+        let history = runner.history();
+        for (end, state) in history.iter().enumerate() {
+            if let Some(start) = history[..end].iter().position(|s| s == state) {
+                return Some(Cycle {
+                    start,
+                    length: end - start,
+                });
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn test_cycle_detection() {
+        for modulus in [7, 10, 31, 97, 255, 1000] {
+            for value in 1..modulus {
+                let sequence = Sequence { value, modulus };
+                let expected = brute_force_cycle(value, modulus);
+                assert_eq!(expected, detect_cycle_brent(&sequence, None).unwrap());
+                assert_eq!(expected, detect_cycle_floyd(&sequence, None).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_runner_limits_and_history() {
+        // 3 -> 10 -> 101 -> 202 -> ... (mod 1000)
+        let mut runner = SimulationRunner::new(Sequence {
+            value: 3,
+            modulus: 1000,
+        })
+        .with_history()
+        .with_step_limit(3);
+        assert_eq!(Outcome::StepLimit(3), runner.run().unwrap());
+        assert_eq!(&[3, 10, 101, 202], runner.history());
+        assert_eq!(Some(&101), runner.replay(2));
+        assert_eq!(None, runner.replay(4));
+
+        // 1 -> 2 -> 0 (mod 5)
+        let mut runner = SimulationRunner::new(Sequence {
+            value: 1,
+            modulus: 5,
+        });
+        assert_eq!(Outcome::Terminal(2), runner.run().unwrap());
+        assert_eq!(0, runner.simulation().value);
+        assert!(runner.history().is_empty());
+    }
+}
@@ -1,4 +1,15 @@
-use std::{any, fs, io, str::FromStr};
+use std::{
+    any,
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    hash::Hash,
+    io::{self, BufRead},
+    ops::Range,
+    path::Path,
+    str::FromStr,
+};
+
+use hashbrown::{HashMap, HashSet};
 
 /// Unsigned 2-d index.
 pub type Vec2u = (usize, usize);
@@ -8,7 +19,56 @@ pub type Vec2i = (isize, isize);
 #[inline]
 /// Read the input for the specific day, given by the integer.
 pub fn read_input_for_day(day: i32) -> io::Result<Vec<String>> {
-    let result = fs::read_to_string(format!("input/day{day}.txt"))?
+    read_input_from_path(format!("input/day{day}.txt"))
+}
+
+/// Load the input for the day, preferring the input embedded in the binary if there is one.
+pub fn load_input_for_day(day: i32) -> Result<Vec<String>, String> {
+    #[cfg(feature = "embedded_inputs")]
+    if let Some(data) = embedded::input_for_day(day) {
+        return Ok(data.lines().map(String::from).collect());
+    }
+
+    read_input_for_day(day).map_err(|_| missing_input_error(day))
+}
+
+/// Open the input for the day as a reader, preferring the input embedded in the binary if there
+/// is one. The input is read as it is consumed, instead of being loaded as lines.
+pub fn open_input_for_day(day: i32) -> Result<Box<dyn BufRead>, String> {
+    #[cfg(feature = "embedded_inputs")]
+    if let Some(data) = embedded::input_for_day(day) {
+        return Ok(Box::new(data.as_bytes()));
+    }
+
+    let file =
+        fs::File::open(format!("input/day{day}.txt")).map_err(|_| missing_input_error(day))?;
+    Ok(Box::new(io::BufReader::new(file)))
+}
+
+/// The error for a day without an input to read.
+fn missing_input_error(day: i32) -> String {
+    if cfg!(feature = "embedded_inputs") {
+        format!(
+            "No input embedded for day {} and failed to read input/day{}.txt. Run `fetch {}` to download it",
+            day, day, day
+        )
+    } else {
+        format!(
+            "Failed to read input data for day {}. Run `fetch {}` to download it",
+            day, day
+        )
+    }
+}
+
+#[cfg(feature = "embedded_inputs")]
+mod embedded {
+    include!(concat!(env!("OUT_DIR"), "/embedded_inputs.rs"));
+}
+
+#[inline]
+/// Read an input file from any path into a list of lines.
+pub fn read_input_from_path<P: AsRef<Path>>(path: P) -> io::Result<Vec<String>> {
+    let result = fs::read_to_string(path)?
         .lines()
         .map(String::from)
         .collect();
@@ -134,3 +194,596 @@ pub fn ortho_dir(v: (isize, isize), clockwise: bool) -> (isize, isize) {
         (-v.1, v.0)
     }
 }
+
+/// Sort unsigned values in linear time. Values spanning a range no larger than their number are
+/// sorted by counting them, and all others by a radix sort on their bytes.
+pub fn sort_unsigned(values: &mut [u32]) {
+    // Below this length the comparison sort is faster than any bookkeeping
+    if values.len() < 64 {
+        values.sort_unstable();
+        return;
+    }
+
+    let min = *values.iter().min().unwrap();
+    let max = *values.iter().max().unwrap();
+    let range = (max - min) as usize + 1;
+    if range <= values.len() {
+        counting_sort(values, min, range);
+    } else {
+        radix_sort(values, max);
+    }
+}
+
+/// Sort values in `min..min + range` by counting the occurrences of each value.
+fn counting_sort(values: &mut [u32], min: u32, range: usize) {
+    let mut counts = vec![0usize; range];
+    for &val in values.iter() {
+        counts[(val - min) as usize] += 1;
+    }
+
+    let mut start = 0;
+    for (offset, &count) in counts.iter().enumerate() {
+        values[start..start + count].fill(min + offset as u32);
+        start += count;
+    }
+}
+
+/// Least significant digit radix sort, one byte per pass. Bytes above the highest byte of the
+/// max are zero for all values, so they are skipped.
+fn radix_sort(values: &mut [u32], max: u32) {
+    let mut buffer = vec![0; values.len()];
+    let mut src: &mut [u32] = values;
+    let mut dst: &mut [u32] = &mut buffer;
+    let mut num_passes = 0;
+    let mut shift = 0;
+    while shift < u32::BITS && max >> shift != 0 {
+        let mut offsets = [0usize; 256];
+        for &val in src.iter() {
+            offsets[(val >> shift & 0xff) as usize] += 1;
+        }
+        let mut start = 0;
+        for offset in offsets.iter_mut() {
+            let count = *offset;
+            *offset = start;
+            start += count;
+        }
+
+        for &val in src.iter() {
+            let digit = (val >> shift & 0xff) as usize;
+            dst[offsets[digit]] = val;
+            offsets[digit] += 1;
+        }
+
+        std::mem::swap(&mut src, &mut dst);
+        num_passes += 1;
+        shift += 8;
+    }
+
+    // After an odd number of passes the sorted values are in the buffer
+    if num_passes % 2 == 1 {
+        dst.copy_from_slice(src);
+    }
+}
+
+/// Disjoint sets of the indices `0..len`, with union by size and path halving.
+#[derive(Clone, Debug)]
+pub struct UnionFind {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+    num_sets: usize,
+}
+
+impl UnionFind {
+    /// Create `len` sets with a single element each.
+    pub fn new(len: usize) -> UnionFind {
+        UnionFind {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+            num_sets: len,
+        }
+    }
+
+    /// Find the representative element of the set containing the element.
+    pub fn find(&mut self, mut element: usize) -> usize {
+        while self.parents[element] != element {
+            self.parents[element] = self.parents[self.parents[element]];
+            element = self.parents[element];
+        }
+        element
+    }
+
+    /// Merge the sets containing the two elements. Returns false if already in the same set.
+    pub fn union(&mut self, first: usize, second: usize) -> bool {
+        let (mut first, mut second) = (self.find(first), self.find(second));
+        if first == second {
+            return false;
+        }
+        if self.sizes[first] < self.sizes[second] {
+            (first, second) = (second, first);
+        }
+        self.parents[second] = first;
+        self.sizes[first] += self.sizes[second];
+        self.num_sets -= 1;
+        true
+    }
+
+    /// Check if the two elements are in the same set.
+    pub fn connected(&mut self, first: usize, second: usize) -> bool {
+        self.find(first) == self.find(second)
+    }
+
+    /// The number of elements in the set containing the element.
+    pub fn set_size(&mut self, element: usize) -> usize {
+        let root = self.find(element);
+        self.sizes[root]
+    }
+
+    /// The number of disjoint sets.
+    pub fn num_sets(&self) -> usize {
+        self.num_sets
+    }
+}
+
+/// A prefix tree of sequences, such as the bytes of strings.
+#[derive(Clone, Debug)]
+pub struct Trie<K> {
+    nodes: Vec<TrieNode<K>>,
+}
+
+#[derive(Clone, Debug)]
+struct TrieNode<K> {
+    children: HashMap<K, usize>,
+    terminal: bool,
+}
+
+impl<K> TrieNode<K> {
+    fn new() -> TrieNode<K> {
+        TrieNode {
+            children: HashMap::new(),
+            terminal: false,
+        }
+    }
+}
+
+impl<K: Eq + Hash> Default for Trie<K> {
+    fn default() -> Self {
+        Trie::new()
+    }
+}
+
+impl<K: Eq + Hash> Trie<K> {
+    /// Create an empty trie.
+    pub fn new() -> Trie<K> {
+        Trie {
+            nodes: vec![TrieNode::new()],
+        }
+    }
+
+    /// Insert a sequence. Returns false if it was already in the trie.
+    pub fn insert<I: IntoIterator<Item = K>>(&mut self, sequence: I) -> bool {
+        let mut node = 0;
+        for key in sequence {
+            node = match self.nodes[node].children.get(&key) {
+                Some(&child) => child,
+                None => {
+                    self.nodes.push(TrieNode::new());
+                    let child = self.nodes.len() - 1;
+                    self.nodes[node].children.insert(key, child);
+                    child
+                }
+            };
+        }
+
+        !std::mem::replace(&mut self.nodes[node].terminal, true)
+    }
+
+    /// Check if the exact sequence is in the trie.
+    pub fn contains<I: IntoIterator<Item = K>>(&self, sequence: I) -> bool {
+        let mut node = 0;
+        for key in sequence {
+            match self.nodes[node].children.get(&key) {
+                Some(&child) => node = child,
+                None => return false,
+            }
+        }
+        self.nodes[node].terminal
+    }
+
+    /// Find the lengths of all sequences in the trie that are a prefix of the sequence, shortest first.
+    pub fn prefix_lengths<I: IntoIterator<Item = K>>(&self, sequence: I) -> Vec<usize> {
+        let mut lengths = Vec::new();
+        let mut node = 0;
+        for (i, key) in sequence.into_iter().enumerate() {
+            match self.nodes[node].children.get(&key) {
+                Some(&child) => node = child,
+                None => break,
+            }
+            if self.nodes[node].terminal {
+                lengths.push(i + 1);
+            }
+        }
+        lengths
+    }
+}
+
+/// A set of integers stored as sorted, disjoint and non-adjacent half-open ranges.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IntervalSet {
+    /// Map from the start to the end of each range.
+    ranges: BTreeMap<usize, usize>,
+    /// Map from a length to the starts of the ranges with that length.
+    by_len: BTreeMap<usize, BTreeSet<usize>>,
+}
+
+impl IntervalSet {
+    /// Create an empty set.
+    pub fn new() -> IntervalSet {
+        IntervalSet {
+            ranges: BTreeMap::new(),
+            by_len: BTreeMap::new(),
+        }
+    }
+
+    /// Store a range in both maps.
+    fn add_range(&mut self, start: usize, end: usize) {
+        self.ranges.insert(start, end);
+        self.by_len.entry(end - start).or_default().insert(start);
+    }
+
+    /// Drop the range starting at `start` from both maps.
+    fn remove_range(&mut self, start: usize) {
+        if let Some(end) = self.ranges.remove(&start) {
+            if let Some(starts) = self.by_len.get_mut(&(end - start)) {
+                starts.remove(&start);
+                if starts.is_empty() {
+                    self.by_len.remove(&(end - start));
+                }
+            }
+        }
+    }
+
+    /// Add all values in the range, merging it with overlapping and adjacent ranges.
+    pub fn insert(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        let (mut start, mut end) = (range.start, range.end);
+        if let Some((&prev_start, &prev_end)) = self.ranges.range(..=start).next_back() {
+            if prev_end >= start {
+                start = prev_start;
+                end = end.max(prev_end);
+            }
+        }
+        let overlapping: Vec<(usize, usize)> = self
+            .ranges
+            .range(start..=end)
+            .map(|(&s, &e)| (s, e))
+            .collect();
+        for (s, e) in overlapping {
+            self.remove_range(s);
+            end = end.max(e);
+        }
+        self.add_range(start, end);
+    }
+
+    /// Remove all values in the range, splitting ranges that contain it.
+    pub fn remove(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        let first = self
+            .ranges
+            .range(..range.start)
+            .next_back()
+            .map_or(
+                range.start,
+                |(&s, &e)| if e > range.start { s } else { range.start },
+            );
+        let overlapping: Vec<(usize, usize)> = self
+            .ranges
+            .range(first..range.end)
+            .map(|(&s, &e)| (s, e))
+            .collect();
+        for (s, e) in overlapping {
+            self.remove_range(s);
+            if s < range.start {
+                self.add_range(s, range.start);
+            }
+            if e > range.end {
+                self.add_range(range.end, e);
+            }
+        }
+    }
+
+    /// Check if the value is in the set.
+    pub fn contains(&self, value: usize) -> bool {
+        self.ranges
+            .range(..=value)
+            .next_back()
+            .is_some_and(|(_, &end)| value < end)
+    }
+
+    /// Find the start of the first range with at least `len` values. Only the first range of
+    /// each length that fits is looked at, instead of every range in the set.
+    pub fn first_fit(&self, len: usize) -> Option<usize> {
+        self.by_len
+            .range(len..)
+            .filter_map(|(_, starts)| starts.first().copied())
+            .min()
+    }
+
+    /// Iterate over the ranges in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        self.ranges.iter().map(|(&start, &end)| start..end)
+    }
+
+    /// The number of values in the set.
+    pub fn len(&self) -> usize {
+        self.ranges.iter().map(|(start, end)| end - start).sum()
+    }
+
+    /// Check if the set has no values.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+}
+
+/// A set of grid positions, such as walls or obstacles.
+pub trait PositionSet {
+    /// Check if the position is in the set.
+    fn contains(&self, pos: &Vec2u) -> bool;
+
+    /// Add a position. Returns false if it was already in the set.
+    fn insert(&mut self, pos: Vec2u) -> bool;
+
+    /// Remove a position. Returns false if it was not in the set.
+    fn remove(&mut self, pos: &Vec2u) -> bool;
+}
+
+impl PositionSet for HashSet<Vec2u> {
+    fn contains(&self, pos: &Vec2u) -> bool {
+        HashSet::contains(self, pos)
+    }
+
+    fn insert(&mut self, pos: Vec2u) -> bool {
+        HashSet::insert(self, pos)
+    }
+
+    fn remove(&mut self, pos: &Vec2u) -> bool {
+        HashSet::remove(self, pos)
+    }
+}
+
+/// A set of positions within known bounds, stored as one bit per cell.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitGrid {
+    size: Vec2u,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    /// Create an empty grid of the given size (rows, cols).
+    pub fn new(size: Vec2u) -> BitGrid {
+        BitGrid {
+            size,
+            words: vec![0; (size.0 * size.1).div_ceil(64)],
+        }
+    }
+
+    /// Create a grid with the positions set. Positions outside the grid are ignored.
+    pub fn from_positions<I: IntoIterator<Item = Vec2u>>(size: Vec2u, positions: I) -> BitGrid {
+        let mut grid = BitGrid::new(size);
+        for pos in positions {
+            grid.set(pos);
+        }
+        grid
+    }
+
+    /// The size of the grid as (rows, cols).
+    pub fn size(&self) -> Vec2u {
+        self.size
+    }
+
+    /// Get the word index and bit mask of a position, if inside the grid.
+    #[inline]
+    fn bit(&self, pos: &Vec2u) -> Option<(usize, u64)> {
+        (pos.0 < self.size.0 && pos.1 < self.size.1).then(|| {
+            let index = pos.0 * self.size.1 + pos.1;
+            (index / 64, 1 << (index % 64))
+        })
+    }
+
+    /// Check if the position is set. Positions outside the grid are never set.
+    #[inline]
+    pub fn contains(&self, pos: &Vec2u) -> bool {
+        self.bit(pos)
+            .is_some_and(|(word, mask)| self.words[word] & mask != 0)
+    }
+
+    /// Set a position. Returns false if it was already set or is outside the grid.
+    pub fn set(&mut self, pos: Vec2u) -> bool {
+        match self.bit(&pos) {
+            Some((word, mask)) => {
+                let was_set = self.words[word] & mask != 0;
+                self.words[word] |= mask;
+                !was_set
+            }
+            None => false,
+        }
+    }
+
+    /// Clear a position. Returns false if it was not set.
+    pub fn clear(&mut self, pos: &Vec2u) -> bool {
+        match self.bit(pos) {
+            Some((word, mask)) => {
+                let was_set = self.words[word] & mask != 0;
+                self.words[word] &= !mask;
+                was_set
+            }
+            None => false,
+        }
+    }
+
+    /// The number of set positions.
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Check if no position is set.
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// Iterate over the set positions, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = Vec2u> + '_ {
+        (0..self.size.0 * self.size.1)
+            .filter(|index| self.words[index / 64] & (1 << (index % 64)) != 0)
+            .map(|index| (index / self.size.1, index % self.size.1))
+    }
+}
+
+impl PositionSet for BitGrid {
+    fn contains(&self, pos: &Vec2u) -> bool {
+        BitGrid::contains(self, pos)
+    }
+
+    fn insert(&mut self, pos: Vec2u) -> bool {
+        self.set(pos)
+    }
+
+    fn remove(&mut self, pos: &Vec2u) -> bool {
+        self.clear(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::input::testing::Rng;
+
+    #[test]
+    fn test_sort_unsigned() {
+        let mut rng = Rng::new(41);
+        for (len, range) in [
+            (10, 100),
+            (1000, 500),
+            (1000, 1 << 12),
+            (5000, u32::MAX - 7),
+        ] {
+            let mut values: Vec<u32> = (0..len)
+                .map(|_| 7 + rng.range(0, range as usize) as u32)
+                .collect();
+            let mut expected = values.clone();
+            expected.sort_unstable();
+
+            sort_unsigned(&mut values);
+            assert_eq!(expected, values, "Failed to sort {} values", len);
+        }
+    }
+
+    #[test]
+    fn test_union_find() {
+        let mut sets = UnionFind::new(6);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(2, 3));
+        assert!(sets.union(1, 3));
+        assert!(!sets.union(0, 2));
+
+        assert!(sets.connected(0, 3));
+        assert!(!sets.connected(0, 4));
+        assert_eq!(4, sets.set_size(2));
+        assert_eq!(1, sets.set_size(5));
+        assert_eq!(3, sets.num_sets());
+    }
+
+    #[test]
+    fn test_trie() {
+        let mut trie = Trie::new();
+        for word in ["r", "wr", "b", "g", "bwu", "rb", "gb", "br"] {
+            assert!(trie.insert(word.bytes()));
+        }
+        assert!(!trie.insert("rb".bytes()));
+
+        assert!(trie.contains("bwu".bytes()));
+        assert!(!trie.contains("bw".bytes()));
+        assert!(!trie.contains("".bytes()));
+        assert_eq!(vec![1, 2], trie.prefix_lengths("rbg".bytes()));
+        assert_eq!(vec![1, 3], trie.prefix_lengths("bwurrg".bytes()));
+        assert!(trie.prefix_lengths("ubwu".bytes()).is_empty());
+    }
+
+    #[test]
+    fn test_interval_set() {
+        let mut set = IntervalSet::new();
+        set.insert(2..4);
+        set.insert(8..10);
+        set.insert(4..5);
+        set.insert(12..12);
+        assert_eq!(vec![2..5, 8..10], set.iter().collect::<Vec<_>>());
+
+        set.insert(3..9);
+        assert_eq!(vec![2..10], set.iter().collect::<Vec<_>>());
+
+        set.remove(4..6);
+        set.remove(0..3);
+        assert_eq!(vec![3..4, 6..10], set.iter().collect::<Vec<_>>());
+        assert_eq!(5, set.len());
+        assert!(set.contains(3) && set.contains(9));
+        assert!(!set.contains(4) && !set.contains(10));
+
+        assert_eq!(Some(3), set.first_fit(1));
+        assert_eq!(Some(6), set.first_fit(2));
+        assert_eq!(None, set.first_fit(5));
+
+        // The first fit is the leftmost range, not the smallest one that fits
+        set.insert(20..22);
+        set.insert(0..2);
+        assert_eq!(Some(0), set.first_fit(2));
+        set.remove(1..2);
+        assert_eq!(Some(6), set.first_fit(2));
+        assert_eq!(Some(6), set.first_fit(4));
+        set.insert(4..6);
+        assert_eq!(Some(3), set.first_fit(4));
+
+        set.remove(0..30);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_bit_grid() {
+        let positions = [(0, 0), (1, 64), (2, 3), (2, 64)];
+        let mut grid = BitGrid::from_positions((3, 65), positions.into_iter().chain([(3, 0)]));
+        assert_eq!(4, grid.len());
+        assert_eq!(positions.to_vec(), grid.iter().collect::<Vec<_>>());
+
+        assert!(grid.contains(&(1, 64)));
+        assert!(!grid.contains(&(1, 63)));
+        assert!(!grid.contains(&(0, 65)));
+
+        assert!(!grid.set((2, 3)));
+        assert!(!grid.set((5, 5)));
+        assert!(grid.clear(&(2, 3)));
+        assert!(!grid.clear(&(2, 3)));
+
+        // Both representations behave the same through the trait
+        let mut hashed: HashSet<Vec2u> = grid.iter().collect();
+        for pos in [(0, 0), (1, 1), (2, 64)] {
+            assert_eq!(
+                PositionSet::insert(&mut hashed, pos),
+                PositionSet::insert(&mut grid, pos)
+            );
+            assert_eq!(
+                PositionSet::remove(&mut hashed, &(0, 0)),
+                PositionSet::remove(&mut grid, &(0, 0))
+            );
+        }
+        assert_eq!(hashed.len(), grid.len());
+        assert!(hashed.iter().all(|pos| grid.contains(pos)));
+
+        grid = BitGrid::new((2, 2));
+        assert!(grid.is_empty());
+    }
+}